@@ -1,20 +1,318 @@
+use loki::{audit::AuditLog, Credentials, Loki};
+use profile::Profile;
 use serde::{Deserialize, Serialize};
 
 /// Contains all the code for interacting with loki
 pub mod loki;
+/// Named Lokis to switch between, and importing them from Grafana
+pub mod profile;
 /// Contains all the code for the user interface
 pub mod ui;
 
 /// The configuration for the Loki client
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct LokiConfig {
     pub loki_url: String,
+    #[serde(default)]
+    pub loki_username: Option<String>,
+    #[serde(default)]
+    pub loki_password: Option<String>,
+    /// Takes precedence over basic auth if both are set
+    #[serde(default)]
+    pub loki_bearer_token: Option<String>,
+    #[serde(default)]
+    pub loki_tenant_id: Option<String>,
+    /// Seconds Loki waits for late lines while tailing
+    #[serde(default)]
+    pub tail_delay_for: u32,
+    /// A YAML or TOML file with saved queries, e.g. one shared in a team repository
+    #[serde(default)]
+    pub saved_queries_path: Option<String>,
+    /// The most log lines read when paging through results
+    #[serde(default = "default_max_lines")]
+    pub max_lines: usize,
+    /// Refuses deletes and pushes, e.g. for support engineers pointed at production.
+    /// Can also be forced for a run with `LOKI_READ_ONLY=true` or `--read-only`, see [`LokiConfig::read_only_override`].
+    #[serde(default)]
+    pub read_only: bool,
+    /// The name of the profile the settings above were taken from
+    #[serde(default)]
+    pub active_profile: Option<String>,
+    #[serde(default)]
+    pub profiles: Vec<Profile>,
+}
+
+fn default_max_lines() -> usize {
+    5000
+}
+
+impl LokiConfig {
+    /// Creates a Loki client for this configuration
+    pub fn loki(&self) -> Loki {
+        let mut loki = Loki::new(self.loki_url.clone());
+        loki.credentials = match (&self.loki_bearer_token, &self.loki_username) {
+            (Some(token), _) => Credentials::Bearer(token.clone()),
+            (None, Some(username)) => Credentials::Basic {
+                username: username.clone(),
+                password: self.loki_password.clone().unwrap_or_default(),
+            },
+            (None, None) => Credentials::None,
+        };
+        loki.tenant_id = self.loki_tenant_id.clone();
+        loki.audit_log = AuditLog::default_location();
+        loki.read_only = self.read_only || self.profile().is_some_and(|profile| profile.read_only);
+        loki
+    }
+
+    /// The active profile, if the settings were taken from one
+    pub fn profile(&self) -> Option<&Profile> {
+        let name = self.active_profile.as_ref()?;
+        self.profiles.iter().find(|profile| &profile.name == name)
+    }
+
+    /// Takes over the connection and defaults of the profile called `name`
+    pub fn use_profile(&mut self, name: &str) -> Result<(), String> {
+        let profile = self
+            .profiles
+            .iter()
+            .find(|profile| profile.name == name)
+            .ok_or_else(|| format!("There is no profile called {name:?}"))?
+            .clone();
+        self.loki_url = profile.loki_url;
+        self.loki_username = profile.loki_username;
+        self.loki_password = profile.loki_password;
+        self.loki_bearer_token = profile.loki_bearer_token;
+        self.loki_tenant_id = profile.loki_tenant_id;
+        if let Some(tail_delay_for) = profile.tail_delay_for {
+            self.tail_delay_for = tail_delay_for;
+        }
+        if profile.saved_queries_path.is_some() {
+            self.saved_queries_path = profile.saved_queries_path;
+        }
+        if let Some(max_lines) = profile.max_lines {
+            self.max_lines = max_lines;
+        }
+        self.active_profile = Some(profile.name);
+        Ok(())
+    }
+
+    /// Writes the current settings back into the active profile, e.g. after editing them
+    pub fn update_profile(&mut self) {
+        let Some(name) = self.active_profile.clone() else {
+            return;
+        };
+        let Some(profile) = self
+            .profiles
+            .iter_mut()
+            .find(|profile| profile.name == name)
+        else {
+            return;
+        };
+        profile.loki_url = self.loki_url.clone();
+        profile.loki_username = self.loki_username.clone();
+        profile.loki_password = self.loki_password.clone();
+        profile.loki_bearer_token = self.loki_bearer_token.clone();
+        profile.loki_tenant_id = self.loki_tenant_id.clone();
+        profile.tail_delay_for = Some(self.tail_delay_for);
+        profile.saved_queries_path = self.saved_queries_path.clone();
+        profile.max_lines = Some(self.max_lines);
+    }
+
+    /// Adds the profiles, replacing existing ones with the same name
+    pub fn import_profiles(&mut self, profiles: Vec<Profile>) {
+        for profile in profiles {
+            match self.profiles.iter_mut().find(|p| p.name == profile.name) {
+                Some(existing) => *existing = profile,
+                None => self.profiles.push(profile),
+            }
+        }
+    }
+
+    ///
+    /// True if read-only mode is forced for this run, by the `--read-only` flag
+    /// or by `LOKI_READ_ONLY` set to `true` or `1`.
+    pub fn read_only_override(args: impl IntoIterator<Item = String>, env: Option<&str>) -> bool {
+        args.into_iter().any(|arg| arg == "--read-only")
+            || env.is_some_and(|value| matches!(value.trim(), "true" | "1"))
+    }
+}
+
+///
+/// Settings from environment variables like `LOKI_URL` and `MAX_LINES`, named like the fields
+/// of [`LokiConfig`]. They are applied on top of the config file for a single run and not stored.
+#[derive(Debug, Default, Deserialize)]
+pub struct EnvConfig {
+    pub loki_url: Option<String>,
+    pub loki_username: Option<String>,
+    pub loki_password: Option<String>,
+    pub loki_bearer_token: Option<String>,
+    pub loki_tenant_id: Option<String>,
+    pub tail_delay_for: Option<u32>,
+    pub saved_queries_path: Option<String>,
+    pub max_lines: Option<usize>,
+}
+
+impl EnvConfig {
+    /// Reads the settings from the environment of this process
+    pub fn from_env() -> Result<Self, String> {
+        Self::from_vars(std::env::vars())
+    }
+
+    /// Reads the settings from the given variables, failing on values that can't be parsed
+    pub fn from_vars(vars: impl IntoIterator<Item = (String, String)>) -> Result<Self, String> {
+        envy::from_iter(vars).map_err(|e| format!("Invalid environment variable: {e}"))
+    }
+
+    /// Overwrites the settings of `config` that are set in the environment
+    pub fn apply(&self, config: &mut LokiConfig) {
+        if let Some(loki_url) = &self.loki_url {
+            config.loki_url = loki_url.clone();
+        }
+        if self.loki_username.is_some() {
+            config.loki_username = self.loki_username.clone();
+        }
+        if self.loki_password.is_some() {
+            config.loki_password = self.loki_password.clone();
+        }
+        if self.loki_bearer_token.is_some() {
+            config.loki_bearer_token = self.loki_bearer_token.clone();
+        }
+        if self.loki_tenant_id.is_some() {
+            config.loki_tenant_id = self.loki_tenant_id.clone();
+        }
+        if let Some(tail_delay_for) = self.tail_delay_for {
+            config.tail_delay_for = tail_delay_for;
+        }
+        if self.saved_queries_path.is_some() {
+            config.saved_queries_path = self.saved_queries_path.clone();
+        }
+        if let Some(max_lines) = self.max_lines {
+            config.max_lines = max_lines;
+        }
+    }
+
+    ///
+    /// Puts back the values of `stored` for the settings that still have the value from
+    /// the environment, so only settings edited during the run are written to the config file.
+    /// The same is done for the active profile.
+    pub fn unapply(&self, config: &mut LokiConfig, stored: &LokiConfig) {
+        restore(
+            &mut config.loki_url,
+            self.loki_url.clone(),
+            &stored.loki_url,
+        );
+        restore(
+            &mut config.loki_username,
+            self.loki_username.clone().map(Some),
+            &stored.loki_username,
+        );
+        restore(
+            &mut config.loki_password,
+            self.loki_password.clone().map(Some),
+            &stored.loki_password,
+        );
+        restore(
+            &mut config.loki_bearer_token,
+            self.loki_bearer_token.clone().map(Some),
+            &stored.loki_bearer_token,
+        );
+        restore(
+            &mut config.loki_tenant_id,
+            self.loki_tenant_id.clone().map(Some),
+            &stored.loki_tenant_id,
+        );
+        restore(
+            &mut config.tail_delay_for,
+            self.tail_delay_for,
+            &stored.tail_delay_for,
+        );
+        restore(
+            &mut config.saved_queries_path,
+            self.saved_queries_path.clone().map(Some),
+            &stored.saved_queries_path,
+        );
+        restore(&mut config.max_lines, self.max_lines, &stored.max_lines);
+
+        // Saving the settings copies them into the active profile, see `LokiConfig::update_profile`
+        let name = config.active_profile.clone();
+        let profile = config
+            .profiles
+            .iter_mut()
+            .find(|profile| Some(&profile.name) == name.as_ref());
+        let stored_profile = stored
+            .profiles
+            .iter()
+            .find(|profile| Some(&profile.name) == name.as_ref());
+        if let (Some(profile), Some(stored_profile)) = (profile, stored_profile) {
+            self.unapply_profile(profile, stored_profile);
+        }
+    }
+
+    fn unapply_profile(&self, profile: &mut Profile, stored: &Profile) {
+        restore(
+            &mut profile.loki_url,
+            self.loki_url.clone(),
+            &stored.loki_url,
+        );
+        restore(
+            &mut profile.loki_username,
+            self.loki_username.clone().map(Some),
+            &stored.loki_username,
+        );
+        restore(
+            &mut profile.loki_password,
+            self.loki_password.clone().map(Some),
+            &stored.loki_password,
+        );
+        restore(
+            &mut profile.loki_bearer_token,
+            self.loki_bearer_token.clone().map(Some),
+            &stored.loki_bearer_token,
+        );
+        restore(
+            &mut profile.loki_tenant_id,
+            self.loki_tenant_id.clone().map(Some),
+            &stored.loki_tenant_id,
+        );
+        restore(
+            &mut profile.tail_delay_for,
+            self.tail_delay_for.map(Some),
+            &stored.tail_delay_for,
+        );
+        restore(
+            &mut profile.saved_queries_path,
+            self.saved_queries_path.clone().map(Some),
+            &stored.saved_queries_path,
+        );
+        restore(
+            &mut profile.max_lines,
+            self.max_lines.map(Some),
+            &stored.max_lines,
+        );
+    }
+}
+
+/// Replaces `current` with `stored` if it still is the value from the environment
+fn restore<T: Clone + PartialEq>(current: &mut T, env: Option<T>, stored: &T) {
+    if env.as_ref() == Some(current) {
+        *current = stored.clone();
+    }
 }
 
 impl Default for LokiConfig {
     fn default() -> Self {
         Self {
             loki_url: String::from("http://localhost:3100"),
+            loki_username: None,
+            loki_password: None,
+            loki_bearer_token: None,
+            loki_tenant_id: None,
+            tail_delay_for: 0,
+            saved_queries_path: None,
+            max_lines: default_max_lines(),
+            read_only: false,
+            active_profile: None,
+            profiles: Vec::new(),
         }
     }
 }
@@ -0,0 +1,99 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+/// What was done to the data in Loki
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Operation {
+    Delete,
+    Push,
+}
+
+impl std::fmt::Display for Operation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Operation::Delete => write!(f, "delete"),
+            Operation::Push => write!(f, "push"),
+        }
+    }
+}
+
+/// A delete or push as it was sent to Loki
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub timestamp: DateTime<Local>,
+    /// The OS user that ran the client
+    pub user: String,
+    /// The Loki the request was sent to
+    pub url: String,
+    pub tenant: Option<String>,
+    pub operation: Operation,
+    /// The query of a delete, or the labels of the pushed streams
+    pub query: String,
+    /// The range of a delete, or the first and last timestamp of the pushed lines
+    pub start: Option<DateTime<Local>>,
+    pub end: Option<DateTime<Local>>,
+    /// The status Loki accepted the request with, or the error
+    pub response: String,
+}
+
+///
+/// An append only file of the destructive requests sent to Loki.
+/// Stored as json lines, `audit.jsonl` next to the config file by default.
+#[derive(Clone, Debug)]
+pub struct AuditLog {
+    pub path: PathBuf,
+}
+
+impl AuditLog {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// The audit log next to the config file, `None` if there is no config directory
+    pub fn default_location() -> Option<Self> {
+        confy::get_configuration_file_path("loki_ui", None)
+            .ok()
+            .map(|config| Self::new(config.with_file_name("audit.jsonl")))
+    }
+
+    /// Appends a record, creating the file if needed
+    pub fn append(&self, record: &AuditRecord) -> io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        let line = serde_json::to_string(record)?;
+        writeln!(file, "{line}")
+    }
+
+    /// All records, oldest first. A missing file has no records, lines that can't be read are skipped.
+    pub fn records(&self) -> io::Result<Vec<AuditRecord>> {
+        let content = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        Ok(content
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+}
+
+/// The name of the user running the client, from the environment
+pub fn os_user() -> String {
+    ["USER", "USERNAME", "LOGNAME"]
+        .iter()
+        .find_map(|name| std::env::var(name).ok().filter(|user| !user.is_empty()))
+        .unwrap_or_else(|| String::from("unknown"))
+}
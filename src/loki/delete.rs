@@ -0,0 +1,89 @@
+use std::fmt::Display;
+
+use chrono::{DateTime, Local};
+use serde_json::Value;
+
+use super::{error::Error, result::string};
+
+/// The progress of a delete request
+#[derive(Clone, Debug, PartialEq)]
+pub enum DeleteStatus {
+    /// Waiting for the compactor, can still be cancelled during the grace period
+    Received,
+    /// Some of the shards of the request were processed, e.g. `50% processed`
+    Partial(String),
+    Processed,
+    /// Cancelled by us, Loki forgets cancelled requests
+    Cancelled,
+    /// A status this client doesn't know
+    Other(String),
+}
+
+impl DeleteStatus {
+    fn parse(status: &str) -> Self {
+        match status {
+            "received" => Self::Received,
+            "processed" => Self::Processed,
+            "cancelled" => Self::Cancelled,
+            status if status.ends_with("processed") => Self::Partial(status.to_string()),
+            status => Self::Other(status.to_string()),
+        }
+    }
+}
+
+impl Display for DeleteStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Received => write!(f, "pending"),
+            Self::Processed => write!(f, "processed"),
+            Self::Cancelled => write!(f, "cancelled"),
+            Self::Partial(status) | Self::Other(status) => write!(f, "{status}"),
+        }
+    }
+}
+
+/// A delete request as listed by `GET /loki/api/v1/delete`
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeleteRequest {
+    pub request_id: String,
+    pub query: String,
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+    pub created_at: DateTime<Local>,
+    pub status: DeleteStatus,
+}
+
+impl DeleteRequest {
+    /// Parses the list of delete requests
+    pub fn list_from_json(text: &Value) -> Result<Vec<Self>, Error> {
+        text.as_array()
+            .ok_or_else(|| {
+                Error::decode(format!("Expected a list of delete requests, got: {text}"))
+            })?
+            .iter()
+            .map(Self::from_json)
+            .collect()
+    }
+
+    fn from_json(request: &Value) -> Result<Self, Error> {
+        Ok(Self {
+            request_id: string(&request["request_id"], "request_id")?.to_string(),
+            query: string(&request["query"], "query")?.to_string(),
+            start: seconds(&request["start_time"], "start_time")?,
+            end: seconds(&request["end_time"], "end_time")?,
+            created_at: seconds(&request["created_at"], "created_at")?,
+            status: DeleteStatus::parse(string(&request["status"], "status")?),
+        })
+    }
+}
+
+/// Loki sends the times of delete requests as unix seconds with milliseconds
+fn seconds(value: &Value, name: &str) -> Result<DateTime<Local>, Error> {
+    let seconds = value
+        .as_f64()
+        .ok_or_else(|| Error::decode(format!("Expected {name} to be a number, got: {value}")))?;
+    let millis = (seconds * 1000.0).round() as i64;
+    DateTime::from_timestamp_millis(millis)
+        .map(|time| time.with_timezone(&Local))
+        .ok_or_else(|| Error::decode(format!("{name} is out of range: {value}")))
+}
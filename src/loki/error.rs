@@ -1,52 +1,135 @@
 use std::fmt::Display;
 
+use serde_json::Value;
+
+/// The underlying error of a transport or decode failure
+pub type Source = Box<dyn std::error::Error + Send + Sync>;
+
+/// Everything that can go wrong when talking to Loki
 #[derive(Debug)]
-pub struct Error {
-    pub source: Option<Box<dyn std::error::Error>>,
-    pub message: String,
+pub enum Error {
+    /// Loki could not be reached, or the connection broke down
+    Transport(Source),
+    /// Loki answered with an error status and a plain text body
+    Status { status: u16, body: String },
+    /// Loki answered with an error in its json format, e.g. for an invalid query
+    Api { error_type: String, message: String },
+    /// The response did not have the expected format
+    Decode {
+        message: String,
+        source: Option<Source>,
+    },
+    /// The request could not be built from the given arguments
+    InvalidInput(String),
+    /// The client is read-only and refused to change data, e.g. `"deleting"`
+    ReadOnly(&'static str),
 }
 
 impl Error {
-    pub fn new(message: String) -> Self {
-        Self {
-            source: None,
+    /// A response that could not be read, without an underlying error
+    pub fn decode(message: String) -> Self {
+        Self::Decode {
             message,
+            source: None,
         }
     }
 
-    pub fn with_source(source: Box<dyn std::error::Error>, message: String) -> Self {
-        Self {
-            source: Some(source),
+    /// A response that could not be read because of `source`
+    pub fn decode_with(source: impl Into<Source>, message: String) -> Self {
+        Self::Decode {
             message,
+            source: Some(source.into()),
+        }
+    }
+
+    /// Turns an error status into an `Api` error if the body is one of Loki's json errors
+    pub fn from_status(status: u16, body: String) -> Self {
+        let json: Option<Value> = serde_json::from_str(&body).ok();
+        match json.as_ref().and_then(api_error) {
+            Some(error) => error,
+            None => Self::Status {
+                status,
+                body: body.trim().to_string(),
+            },
         }
     }
 }
 
-impl std::error::Error for Error {
-    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
-        // return source
-        match &self.source {
-            Some(source) => Some(source.as_ref()),
-            None => None,
+/// Reads `{"status": "error", "errorType": "...", "error": "..."}`
+pub(crate) fn api_error(json: &Value) -> Option<Error> {
+    if json["status"].as_str() != Some("error") {
+        return None;
+    }
+    Some(Error::Api {
+        error_type: json["errorType"].as_str().unwrap_or("unknown").to_string(),
+        message: json["error"].as_str().unwrap_or_default().to_string(),
+    })
+}
+
+impl From<ureq::Error> for Error {
+    fn from(error: ureq::Error) -> Self {
+        match error {
+            ureq::Error::Status(status, response) => {
+                Self::from_status(status, response.into_string().unwrap_or_default())
+            }
+            ureq::Error::Transport(transport) => Self::Transport(Box::new(transport)),
         }
     }
+}
 
-    fn description(&self) -> &str {
-        &self.message
+impl From<tungstenite::Error> for Error {
+    fn from(error: tungstenite::Error) -> Self {
+        match error {
+            tungstenite::Error::Http(response) => {
+                let status = response.status().as_u16();
+                let body = response
+                    .into_body()
+                    .map(|body| String::from_utf8_lossy(&body).into_owned())
+                    .unwrap_or_default();
+                Self::from_status(status, body)
+            }
+            error => Self::Transport(Box::new(error)),
+        }
     }
+}
 
-    fn cause(&self) -> Option<&dyn std::error::Error> {
-        self.source()
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Transport(source)
+            | Self::Decode {
+                source: Some(source),
+                ..
+            } => Some(source.as_ref()),
+            _ => None,
+        }
     }
 }
 
 impl Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        // Iterate over source and print to_string()
-        writeln!(f, "{}", self.message)?;
-        if let Some(source) = &self.source {
-            write!(f, "{}", source)?;
+        match self {
+            Self::Transport(source) => write!(f, "Error connecting to Loki: {source}"),
+            Self::Status { status, body } if body.is_empty() => {
+                write!(f, "Loki answered with status {status}")
+            }
+            Self::Status { status, body } => {
+                write!(f, "Loki answered with status {status}:\n{body}")
+            }
+            Self::Api {
+                error_type,
+                message,
+            } => write!(f, "Loki rejected the request ({error_type}):\n{message}"),
+            Self::Decode {
+                message,
+                source: Some(source),
+            } => write!(f, "{message}:\n{source}"),
+            Self::Decode { message, .. } | Self::InvalidInput(message) => {
+                write!(f, "{message}")
+            }
+            Self::ReadOnly(operation) => {
+                write!(f, "Read-only mode, {operation} is disabled")
+            }
         }
-        Ok(())
     }
 }
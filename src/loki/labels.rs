@@ -0,0 +1,126 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Display,
+};
+
+use super::error::Error;
+
+///
+/// A set of labels with valid names, e.g. for a stream to push to.
+/// Renders as the canonical Loki label string `{app="api", env="prod"}`,
+/// with the names sorted and the values escaped, which also is a stream selector.
+///
+/// # Example
+/// ```
+/// use loki_ui::loki::labels::Labels;
+///
+/// let labels = Labels::new().with("job", "test").unwrap().with("msg", "say \"hi\"").unwrap();
+/// assert_eq!(labels.to_string(), r#"{job="test", msg="say \"hi\""}"#);
+/// assert!(Labels::new().with("not-valid", "x").is_err());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Labels {
+    labels: BTreeMap<String, String>,
+}
+
+impl Labels {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a label, replacing any previous value for the name
+    pub fn insert(&mut self, name: &str, value: &str) -> Result<(), Error> {
+        validate_name(name)?;
+        self.labels.insert(name.to_string(), value.to_string());
+        Ok(())
+    }
+
+    /// Adds a label, for building label sets in one expression
+    pub fn with(mut self, name: &str, value: &str) -> Result<Self, Error> {
+        self.insert(name, value)?;
+        Ok(self)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.labels.get(name).map(String::as_str)
+    }
+
+    /// The labels sorted by name
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.labels
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+}
+
+impl Display for Labels {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", format(self.iter()))
+    }
+}
+
+impl TryFrom<&HashMap<String, String>> for Labels {
+    type Error = Error;
+
+    /// Takes over labels as Loki returns them, e.g. the labels of a stream
+    fn try_from(labels: &HashMap<String, String>) -> Result<Self, Error> {
+        let mut result = Labels::new();
+        for (name, value) in labels {
+            result.insert(name, value)?;
+        }
+        Ok(result)
+    }
+}
+
+/// Checks that a name matches `[a-zA-Z_][a-zA-Z0-9_]*`, like Loki requires
+pub fn validate_name(name: &str) -> Result<(), Error> {
+    let mut chars = name.chars();
+    let valid = match chars.next() {
+        Some(first) => {
+            (first.is_ascii_alphabetic() || first == '_')
+                && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        None => false,
+    };
+    match valid {
+        true => Ok(()),
+        false => Err(Error::InvalidInput(format!("Invalid label name: {name:?}"))),
+    }
+}
+
+///
+/// Renders label pairs the canonical way, sorted by name and with escaped values.
+/// For labels as Loki returns them, whose names are not validated again.
+pub fn format<'a>(labels: impl IntoIterator<Item = (&'a str, &'a str)>) -> String {
+    let mut labels: Vec<_> = labels.into_iter().collect();
+    labels.sort();
+    let labels: Vec<String> = labels
+        .into_iter()
+        .map(|(name, value)| format!("{name}=\"{}\"", escape(value)))
+        .collect();
+    format!("{{{}}}", labels.join(", "))
+}
+
+/// Escapes a value for use inside a double quoted LogQL string
+pub fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for char in value.chars() {
+        match char {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            char => escaped.push(char),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,306 @@
+use std::{ops::Range, time::Duration};
+
+/// A complete LogQL query
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    /// Returns log lines
+    Log(LogExpr),
+    /// Returns samples
+    Metric(MetricExpr),
+}
+
+impl Expr {
+    /// The first stream selector of the query, `None` for queries like `vector(1)`
+    pub fn selector(&self) -> Option<&Selector> {
+        match self {
+            Expr::Log(log) => Some(&log.selector),
+            Expr::Metric(metric) => metric.selector(),
+        }
+    }
+}
+
+/// A stream selector followed by a pipeline, e.g. `{job="a"} |= "error" | json`
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogExpr {
+    pub selector: Selector,
+    pub pipeline: Vec<Stage>,
+    pub span: Range<usize>,
+}
+
+/// The `{...}` part of a query
+#[derive(Debug, Clone, PartialEq)]
+pub struct Selector {
+    pub matchers: Vec<Matcher>,
+    pub span: Range<usize>,
+}
+
+/// A single `name op "value"` comparison
+#[derive(Debug, Clone, PartialEq)]
+pub struct Matcher {
+    pub name: String,
+    pub op: MatchOp,
+    pub value: String,
+    pub span: Range<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchOp {
+    /// `=`
+    Eq,
+    /// `!=`
+    Neq,
+    /// `=~`
+    Re,
+    /// `!~`
+    Nre,
+}
+
+/// A stage of a log pipeline
+#[derive(Debug, Clone, PartialEq)]
+pub enum Stage {
+    /// `|= "a"`, `!~ "b"`, `|= "a" or "b"`, `|= ip("...")`
+    LineFilter {
+        op: LineFilterOp,
+        values: Vec<LineFilterValue>,
+        span: Range<usize>,
+    },
+    /// `| json`, `| logfmt`, `| regexp "..."`, `| pattern "..."`, `| unpack`
+    Parser {
+        parser: ParserKind,
+        /// Flags like `--strict` for logfmt
+        flags: Vec<String>,
+        /// Extracted labels like `| json status="response.status"`
+        params: Vec<LabelExtraction>,
+        span: Range<usize>,
+    },
+    /// `| level="error"`, `| status >= 400 and duration > 1s`
+    LabelFilter {
+        filter: LabelFilter,
+        span: Range<usize>,
+    },
+    /// `| line_format "{{.msg}}"`
+    LineFormat {
+        template: String,
+        span: Range<usize>,
+    },
+    /// `| label_format dst=src, other="{{.a}}"`
+    LabelFormat {
+        labels: Vec<LabelExtraction>,
+        span: Range<usize>,
+    },
+    /// `| drop a, b="c"`
+    Drop {
+        labels: Vec<LabelSelection>,
+        span: Range<usize>,
+    },
+    /// `| keep a, b="c"`
+    Keep {
+        labels: Vec<LabelSelection>,
+        span: Range<usize>,
+    },
+    /// `| decolorize`
+    Decolorize { span: Range<usize> },
+    /// `| unwrap bytes(size)`, only valid inside unwrapped range aggregations
+    Unwrap {
+        label: String,
+        conversion: Option<String>,
+        span: Range<usize>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineFilterOp {
+    /// `|=`
+    Contains,
+    /// `!=`
+    NotContains,
+    /// `|~`
+    Match,
+    /// `!~`
+    NotMatch,
+    /// `|>`
+    Pattern,
+    /// `!>`
+    NotPattern,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LineFilterValue {
+    String(String),
+    /// `ip("192.168.0.0/16")`
+    Ip(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParserKind {
+    Json,
+    Logfmt,
+    Regexp,
+    Pattern,
+    Unpack,
+}
+
+/// `name="expression"` or just `name`, as used by json, logfmt and label_format
+#[derive(Debug, Clone, PartialEq)]
+pub struct LabelExtraction {
+    pub name: String,
+    /// A string expression, or the label to rename for label_format
+    pub value: Option<LabelExtractionValue>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum LabelExtractionValue {
+    String(String),
+    Label(String),
+}
+
+/// A label for drop and keep, optionally only if it matches
+#[derive(Debug, Clone, PartialEq)]
+pub struct LabelSelection {
+    pub name: String,
+    pub matcher: Option<Matcher>,
+}
+
+/// A label filter expression
+#[derive(Debug, Clone, PartialEq)]
+pub enum LabelFilter {
+    Matcher(Matcher),
+    /// `status >= 400`, `duration > 1s`, `size < 10KB`
+    Compare {
+        label: String,
+        op: CompareOp,
+        value: Literal,
+    },
+    /// `addr = ip("10.0.0.0/8")`
+    Ip {
+        label: String,
+        negated: bool,
+        value: String,
+    },
+    And(Box<LabelFilter>, Box<LabelFilter>),
+    Or(Box<LabelFilter>, Box<LabelFilter>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    Neq,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Literal {
+    Number(f64),
+    Duration(Duration),
+    Bytes(u64),
+}
+
+/// A query that returns samples
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetricExpr {
+    /// `rate({job="a"}[5m])`
+    RangeAggregation {
+        function: String,
+        /// The first argument of `quantile_over_time`
+        param: Option<f64>,
+        log: LogExpr,
+        range: Duration,
+        offset: Option<Duration>,
+        grouping: Option<Grouping>,
+        span: Range<usize>,
+    },
+    /// `sum by (job) (...)`, `topk(5, ...)`
+    VectorAggregation {
+        operation: String,
+        param: Option<f64>,
+        expr: Box<MetricExpr>,
+        grouping: Option<Grouping>,
+        span: Range<usize>,
+    },
+    /// `a / b`, `a > bool 10`
+    Binary {
+        op: BinaryOp,
+        /// Comparisons return 0 or 1 instead of filtering
+        bool_modifier: bool,
+        matching: Option<VectorMatching>,
+        left: Box<MetricExpr>,
+        right: Box<MetricExpr>,
+    },
+    /// `label_replace(expr, "dst", "replacement", "src", "regex")`
+    LabelReplace {
+        expr: Box<MetricExpr>,
+        destination: String,
+        replacement: String,
+        source: String,
+        regex: String,
+        span: Range<usize>,
+    },
+    /// `vector(1)`
+    Vector {
+        value: f64,
+        span: Range<usize>,
+    },
+    Number {
+        value: f64,
+        span: Range<usize>,
+    },
+    Parens(Box<MetricExpr>),
+}
+
+impl MetricExpr {
+    /// The first stream selector of the query, `None` for queries like `vector(1)`
+    pub fn selector(&self) -> Option<&Selector> {
+        match self {
+            MetricExpr::RangeAggregation { log, .. } => Some(&log.selector),
+            MetricExpr::VectorAggregation { expr, .. }
+            | MetricExpr::LabelReplace { expr, .. }
+            | MetricExpr::Parens(expr) => expr.selector(),
+            MetricExpr::Binary { left, right, .. } => left.selector().or_else(|| right.selector()),
+            MetricExpr::Vector { .. } | MetricExpr::Number { .. } => None,
+        }
+    }
+}
+
+/// `by (a, b)` or `without (a)`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Grouping {
+    pub without: bool,
+    pub labels: Vec<String>,
+}
+
+/// `on (a)`, `ignoring (b) group_left (c)`
+#[derive(Debug, Clone, PartialEq)]
+pub struct VectorMatching {
+    /// `on` if true, `ignoring` otherwise
+    pub on: bool,
+    pub labels: Vec<String>,
+    pub group: Option<Group>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Group {
+    Left(Vec<String>),
+    Right(Vec<String>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    Or,
+    And,
+    Unless,
+    Eq,
+    Neq,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Pow,
+}
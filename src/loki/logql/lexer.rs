@@ -0,0 +1,292 @@
+use std::{ops::Range, time::Duration};
+
+use super::{parse_bytes, parse_duration, ParseError};
+
+/// A token and the bytes of the query it was read from
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub span: Range<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenKind {
+    /// Label names, keywords and function names
+    Ident(String),
+    /// The unescaped content of a `"..."` or `` `...` `` string
+    String(String),
+    Number(f64),
+    Duration(Duration),
+    Bytes(u64),
+    LBrace,
+    RBrace,
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Comma,
+    /// `|`
+    Pipe,
+    /// `|=`
+    PipeExact,
+    /// `|~`
+    PipeMatch,
+    /// `|>`
+    PipePattern,
+    /// `!>`
+    NotPattern,
+    /// `=`
+    Eq,
+    /// `==`
+    EqEq,
+    /// `!=`
+    Neq,
+    /// `=~`
+    Re,
+    /// `!~`
+    Nre,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+    Pow,
+    /// `--strict` style flags of the logfmt parser
+    Flag(String),
+}
+
+///
+/// Splits a query into tokens.
+/// Stops after the first error, so partial queries can still be highlighted up to it.
+pub struct Lexer<'a> {
+    query: &'a str,
+    pos: usize,
+    failed: bool,
+}
+
+impl<'a> Lexer<'a> {
+    pub fn new(query: &'a str) -> Self {
+        Self {
+            query,
+            pos: 0,
+            failed: false,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.query[self.pos..].chars().next()
+    }
+
+    fn peek_second(&self) -> Option<char> {
+        self.query[self.pos..].chars().nth(1)
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let char = self.peek()?;
+        self.pos += char.len_utf8();
+        Some(char)
+    }
+
+    fn skip_whitespace_and_comments(&mut self) {
+        while let Some(char) = self.peek() {
+            if char.is_whitespace() {
+                self.bump();
+            } else if char == '#' {
+                while let Some(char) = self.bump() {
+                    if char == '\n' {
+                        break;
+                    }
+                }
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn ident(&mut self, start: usize) -> TokenKind {
+        while self.peek().is_some_and(is_ident_char) {
+            self.bump();
+        }
+        TokenKind::Ident(self.query[start..self.pos].to_string())
+    }
+
+    /// Numbers, durations like `1h30m` and byte sizes like `10KB`
+    fn number(&mut self, start: usize) -> Result<TokenKind, ParseError> {
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_alphanumeric() || c == '.' || c == '_')
+        {
+            // Allow exponents like 1e-3
+            let exponent = matches!(self.peek(), Some('e' | 'E'))
+                && matches!(self.peek_second(), Some('-' | '+'));
+            self.bump();
+            if exponent {
+                self.bump();
+            }
+        }
+
+        let text = &self.query[start..self.pos];
+        if let Ok(number) = text.parse::<f64>() {
+            return Ok(TokenKind::Number(number));
+        }
+        if let Some(duration) = parse_duration(text) {
+            return Ok(TokenKind::Duration(duration));
+        }
+        if let Some(bytes) = parse_bytes(text) {
+            return Ok(TokenKind::Bytes(bytes));
+        }
+        Err(ParseError::new(
+            format!("invalid number, duration or byte size: {text}"),
+            start..self.pos,
+        ))
+    }
+
+    fn string(&mut self, start: usize, quote: char) -> Result<TokenKind, ParseError> {
+        let mut value = String::new();
+        loop {
+            let Some(char) = self.bump() else {
+                return Err(ParseError::new(
+                    String::from("unterminated string"),
+                    start..self.pos,
+                ));
+            };
+            if char == quote {
+                return Ok(TokenKind::String(value));
+            }
+            if char != '\\' || quote == '`' {
+                value.push(char);
+                continue;
+            }
+
+            let escape_start = self.pos - 1;
+            let escaped = match self.bump() {
+                Some('n') => '\n',
+                Some('t') => '\t',
+                Some('r') => '\r',
+                Some('a') => '\x07',
+                Some('b') => '\x08',
+                Some('f') => '\x0c',
+                Some('v') => '\x0b',
+                Some('0') => '\0',
+                Some(char @ ('\\' | '"' | '\'')) => char,
+                Some(kind @ ('x' | 'u' | 'U')) => {
+                    let digits = match kind {
+                        'x' => 2,
+                        'u' => 4,
+                        _ => 8,
+                    };
+                    let hex_start = self.pos;
+                    for _ in 0..digits {
+                        self.bump();
+                    }
+                    u32::from_str_radix(&self.query[hex_start..self.pos], 16)
+                        .ok()
+                        .and_then(char::from_u32)
+                        .ok_or_else(|| {
+                            ParseError::new(
+                                String::from("invalid escape sequence"),
+                                escape_start..self.pos,
+                            )
+                        })?
+                }
+                _ => {
+                    return Err(ParseError::new(
+                        String::from("invalid escape sequence"),
+                        escape_start..self.pos,
+                    ))
+                }
+            };
+            value.push(escaped);
+        }
+    }
+
+    fn operator(&mut self, start: usize, char: char) -> Result<TokenKind, ParseError> {
+        let next = self.peek();
+        let (kind, two_chars) = match (char, next) {
+            ('|', Some('=')) => (TokenKind::PipeExact, true),
+            ('|', Some('~')) => (TokenKind::PipeMatch, true),
+            ('|', Some('>')) => (TokenKind::PipePattern, true),
+            ('|', _) => (TokenKind::Pipe, false),
+            ('!', Some('=')) => (TokenKind::Neq, true),
+            ('!', Some('~')) => (TokenKind::Nre, true),
+            ('!', Some('>')) => (TokenKind::NotPattern, true),
+            ('=', Some('=')) => (TokenKind::EqEq, true),
+            ('=', Some('~')) => (TokenKind::Re, true),
+            ('=', _) => (TokenKind::Eq, false),
+            ('>', Some('=')) => (TokenKind::Ge, true),
+            ('>', _) => (TokenKind::Gt, false),
+            ('<', Some('=')) => (TokenKind::Le, true),
+            ('<', _) => (TokenKind::Lt, false),
+            ('{', _) => (TokenKind::LBrace, false),
+            ('}', _) => (TokenKind::RBrace, false),
+            ('(', _) => (TokenKind::LParen, false),
+            (')', _) => (TokenKind::RParen, false),
+            ('[', _) => (TokenKind::LBracket, false),
+            (']', _) => (TokenKind::RBracket, false),
+            (',', _) => (TokenKind::Comma, false),
+            ('+', _) => (TokenKind::Add, false),
+            ('-', _) => (TokenKind::Sub, false),
+            ('*', _) => (TokenKind::Mul, false),
+            ('/', _) => (TokenKind::Div, false),
+            ('%', _) => (TokenKind::Mod, false),
+            ('^', _) => (TokenKind::Pow, false),
+            _ => {
+                return Err(ParseError::new(
+                    format!("unexpected character: {char}"),
+                    start..self.pos,
+                ))
+            }
+        };
+        if two_chars {
+            self.bump();
+        }
+        Ok(kind)
+    }
+}
+
+impl Iterator for Lexer<'_> {
+    type Item = Result<Token, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        self.skip_whitespace_and_comments();
+
+        let start = self.pos;
+        let char = self.bump()?;
+        let kind = match char {
+            '"' | '`' => self.string(start, char),
+            '-' if self.peek() == Some('-') && self.peek_second().is_some_and(is_ident_char) => {
+                self.bump();
+                while self.peek().is_some_and(|c| is_ident_char(c) || c == '-') {
+                    self.bump();
+                }
+                Ok(TokenKind::Flag(self.query[start..self.pos].to_string()))
+            }
+            char if char.is_ascii_digit() => self.number(start),
+            '.' if self.peek().is_some_and(|c| c.is_ascii_digit()) => self.number(start),
+            char if char.is_alphabetic() || char == '_' => Ok(self.ident(start)),
+            char => self.operator(start, char),
+        };
+
+        match kind {
+            Ok(kind) => Some(Ok(Token {
+                kind,
+                span: start..self.pos,
+            })),
+            Err(e) => {
+                self.failed = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+fn is_ident_char(char: char) -> bool {
+    char.is_alphanumeric() || char == '_'
+}
@@ -0,0 +1,160 @@
+use std::{fmt::Display, ops::Range, time::Duration};
+
+use self::{ast::Expr, parser::Parser};
+
+pub mod ast;
+pub mod lexer;
+mod parser;
+
+/// Functions that aggregate a log query over a range, like `rate({job="a"}[5m])`
+pub const RANGE_AGGREGATIONS: &[&str] = &[
+    "rate",
+    "rate_counter",
+    "count_over_time",
+    "bytes_rate",
+    "bytes_over_time",
+    "absent_over_time",
+    "avg_over_time",
+    "sum_over_time",
+    "min_over_time",
+    "max_over_time",
+    "stddev_over_time",
+    "stdvar_over_time",
+    "quantile_over_time",
+    "first_over_time",
+    "last_over_time",
+];
+
+/// Aggregations over the series of a metric query, like `sum by (job) (...)`
+pub const VECTOR_AGGREGATIONS: &[&str] = &[
+    "sum",
+    "avg",
+    "min",
+    "max",
+    "count",
+    "stddev",
+    "stdvar",
+    "topk",
+    "bottomk",
+    "approx_topk",
+    "sort",
+    "sort_desc",
+];
+
+/// A syntax error and the bytes of the query it refers to
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub message: String,
+    pub span: Range<usize>,
+}
+
+impl ParseError {
+    pub fn new(message: String, span: Range<usize>) -> Self {
+        Self { message, span }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at {}", self.message, self.span.start)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+///
+/// Parses a LogQL query.
+/// Only checks the syntax, label names and values are not looked up.
+pub fn parse(query: &str) -> Result<Expr, ParseError> {
+    Parser::new(query)?.parse()
+}
+
+///
+/// Parses a duration like `5m`, `1h30m` or `250ms`.
+/// Supports the units ns, us, µs, ms, s, m, h, d, w and y.
+/// Returns `None` for durations too large to represent.
+pub fn parse_duration(text: &str) -> Option<Duration> {
+    if text.is_empty() {
+        return None;
+    }
+    let mut rest = text;
+    let mut total = 0.0;
+    while !rest.is_empty() {
+        let number_len = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(rest.len());
+        let number: f64 = rest[..number_len].parse().ok()?;
+        rest = &rest[number_len..];
+
+        let unit_len = rest
+            .find(|c: char| c.is_ascii_digit() || c == '.')
+            .unwrap_or(rest.len());
+        let seconds = match &rest[..unit_len] {
+            "ns" => 1e-9,
+            "us" | "µs" => 1e-6,
+            "ms" => 1e-3,
+            "s" => 1.0,
+            "m" => 60.0,
+            "h" => 3600.0,
+            "d" => 86400.0,
+            "w" => 604800.0,
+            "y" => 31536000.0,
+            _ => return None,
+        };
+        rest = &rest[unit_len..];
+        total += number * seconds;
+    }
+    Duration::try_from_secs_f64(total).ok()
+}
+
+/// Formats a duration the way `parse_duration` reads it, e.g. `1h30m` or `250ms`
+pub fn format_duration(duration: Duration) -> String {
+    const UNITS: [(&str, u128); 7] = [
+        ("d", 86_400_000_000_000),
+        ("h", 3_600_000_000_000),
+        ("m", 60_000_000_000),
+        ("s", 1_000_000_000),
+        ("ms", 1_000_000),
+        ("us", 1_000),
+        ("ns", 1),
+    ];
+    let mut rest = duration.as_nanos();
+    if rest == 0 {
+        return String::from("0s");
+    }
+    let mut text = String::new();
+    for (unit, nanos) in UNITS {
+        if rest >= nanos {
+            text.push_str(&format!("{}{unit}", rest / nanos));
+            rest %= nanos;
+        }
+    }
+    text
+}
+
+///
+/// Parses a byte size like `10KB`, `1.5MiB` or `20b`.
+/// KB and friends are powers of 1000, KiB and friends powers of 1024.
+pub fn parse_bytes(text: &str) -> Option<u64> {
+    let number_len = text
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(text.len());
+    let number: f64 = text[..number_len].parse().ok()?;
+    let multiplier: u64 = match text[number_len..].to_lowercase().as_str() {
+        "b" => 1,
+        "kb" => 1000,
+        "mb" => 1000_u64.pow(2),
+        "gb" => 1000_u64.pow(3),
+        "tb" => 1000_u64.pow(4),
+        "pb" => 1000_u64.pow(5),
+        "eb" => 1000_u64.pow(6),
+        "kib" => 1 << 10,
+        "mib" => 1 << 20,
+        "gib" => 1 << 30,
+        "tib" => 1 << 40,
+        "pib" => 1 << 50,
+        "eib" => 1 << 60,
+        _ => return None,
+    };
+    Some((number * multiplier as f64) as u64)
+}
@@ -0,0 +1,839 @@
+use std::{ops::Range, time::Duration};
+
+use super::{
+    ast::*,
+    lexer::{Lexer, Token, TokenKind},
+    ParseError, RANGE_AGGREGATIONS, VECTOR_AGGREGATIONS,
+};
+
+/// The conversion functions `unwrap` accepts
+const UNWRAP_CONVERSIONS: &[&str] = &["bytes", "duration", "duration_seconds"];
+
+pub(super) struct Parser<'a> {
+    query: &'a str,
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    pub(super) fn new(query: &'a str) -> Result<Self, ParseError> {
+        Ok(Self {
+            query,
+            tokens: Lexer::new(query).collect::<Result<_, _>>()?,
+            pos: 0,
+        })
+    }
+
+    pub(super) fn parse(mut self) -> Result<Expr, ParseError> {
+        if self.tokens.is_empty() {
+            return Err(ParseError::new(String::from("query is empty"), 0..0));
+        }
+        let expr = self.expr(0)?;
+        if let Some(token) = self.peek_token() {
+            return Err(ParseError::new(
+                format!("unexpected {}", self.describe(token)),
+                token.span.clone(),
+            ));
+        }
+        Ok(expr)
+    }
+
+    fn peek(&self) -> Option<&TokenKind> {
+        self.tokens.get(self.pos).map(|token| &token.kind)
+    }
+
+    fn peek_token(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn peek_ident(&self) -> Option<&str> {
+        match self.peek() {
+            Some(TokenKind::Ident(ident)) => Some(ident),
+            _ => None,
+        }
+    }
+
+    fn bump(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    /// The span of the next token, or the end of the query
+    fn next_span(&self) -> Range<usize> {
+        match self.peek_token() {
+            Some(token) => token.span.clone(),
+            None => self.query.len()..self.query.len(),
+        }
+    }
+
+    /// The end of the last consumed token
+    fn last_end(&self) -> usize {
+        self.pos
+            .checked_sub(1)
+            .and_then(|pos| self.tokens.get(pos))
+            .map_or(0, |token| token.span.end)
+    }
+
+    fn describe(&self, token: &Token) -> String {
+        format!("\"{}\"", &self.query[token.span.clone()])
+    }
+
+    fn error<T>(&self, expected: &str) -> Result<T, ParseError> {
+        let found = match self.peek_token() {
+            Some(token) => self.describe(token),
+            None => String::from("end of query"),
+        };
+        Err(ParseError::new(
+            format!("expected {expected} but found {found}"),
+            self.next_span(),
+        ))
+    }
+
+    fn eat(&mut self, kind: &TokenKind) -> bool {
+        if self.peek() == Some(kind) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, kind: &TokenKind, expected: &str) -> Result<Range<usize>, ParseError> {
+        if self.peek() == Some(kind) {
+            Ok(self.bump().unwrap().span)
+        } else {
+            self.error(expected)
+        }
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        if self.peek_ident() == Some(keyword) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn ident(&mut self, expected: &str) -> Result<String, ParseError> {
+        match self.peek() {
+            Some(TokenKind::Ident(ident)) => {
+                let ident = ident.clone();
+                self.pos += 1;
+                Ok(ident)
+            }
+            _ => self.error(expected),
+        }
+    }
+
+    fn string(&mut self, expected: &str) -> Result<String, ParseError> {
+        match self.peek() {
+            Some(TokenKind::String(string)) => {
+                let string = string.clone();
+                self.pos += 1;
+                Ok(string)
+            }
+            _ => self.error(expected),
+        }
+    }
+
+    fn number(&mut self) -> Result<f64, ParseError> {
+        let negative = self.eat(&TokenKind::Sub);
+        match self.peek() {
+            Some(TokenKind::Number(number)) => {
+                let number = *number;
+                self.pos += 1;
+                Ok(if negative { -number } else { number })
+            }
+            _ => self.error("a number"),
+        }
+    }
+
+    fn duration(&mut self) -> Result<Duration, ParseError> {
+        match self.peek() {
+            Some(TokenKind::Duration(duration)) => {
+                let duration = *duration;
+                self.pos += 1;
+                Ok(duration)
+            }
+            _ => self.error("a duration like 5m"),
+        }
+    }
+
+    /// Binary expressions, using precedence climbing
+    fn expr(&mut self, min_precedence: u8) -> Result<Expr, ParseError> {
+        let mut left = self.unary()?;
+
+        while let Some((op, precedence)) = self.peek_binary_op() {
+            if precedence < min_precedence {
+                break;
+            }
+            let op_span = self.next_span();
+            self.pos += 1;
+
+            let bool_modifier = self.eat_keyword("bool");
+            let matching = self.vector_matching()?;
+            // ^ is right associative, everything else left associative
+            let next_precedence = if op == BinaryOp::Pow {
+                precedence
+            } else {
+                precedence + 1
+            };
+            let right = self.expr(next_precedence)?;
+
+            let (Expr::Metric(left_metric), Expr::Metric(right)) = (left, right) else {
+                return Err(ParseError::new(
+                    String::from("binary operations are only allowed on metric queries"),
+                    op_span,
+                ));
+            };
+            left = Expr::Metric(MetricExpr::Binary {
+                op,
+                bool_modifier,
+                matching,
+                left: Box::new(left_metric),
+                right: Box::new(right),
+            });
+        }
+        Ok(left)
+    }
+
+    fn peek_binary_op(&self) -> Option<(BinaryOp, u8)> {
+        let op = match self.peek()? {
+            TokenKind::Ident(ident) => match ident.as_str() {
+                "or" => BinaryOp::Or,
+                "and" => BinaryOp::And,
+                "unless" => BinaryOp::Unless,
+                _ => return None,
+            },
+            TokenKind::EqEq => BinaryOp::Eq,
+            TokenKind::Neq => BinaryOp::Neq,
+            TokenKind::Gt => BinaryOp::Gt,
+            TokenKind::Ge => BinaryOp::Ge,
+            TokenKind::Lt => BinaryOp::Lt,
+            TokenKind::Le => BinaryOp::Le,
+            TokenKind::Add => BinaryOp::Add,
+            TokenKind::Sub => BinaryOp::Sub,
+            TokenKind::Mul => BinaryOp::Mul,
+            TokenKind::Div => BinaryOp::Div,
+            TokenKind::Mod => BinaryOp::Mod,
+            TokenKind::Pow => BinaryOp::Pow,
+            _ => return None,
+        };
+        let precedence = match op {
+            BinaryOp::Or => 1,
+            BinaryOp::And | BinaryOp::Unless => 2,
+            BinaryOp::Eq
+            | BinaryOp::Neq
+            | BinaryOp::Gt
+            | BinaryOp::Ge
+            | BinaryOp::Lt
+            | BinaryOp::Le => 3,
+            BinaryOp::Add | BinaryOp::Sub => 4,
+            BinaryOp::Mul | BinaryOp::Div | BinaryOp::Mod => 5,
+            BinaryOp::Pow => 6,
+        };
+        Some((op, precedence))
+    }
+
+    fn vector_matching(&mut self) -> Result<Option<VectorMatching>, ParseError> {
+        let on = match self.peek_ident() {
+            Some("on") => true,
+            Some("ignoring") => false,
+            _ => return Ok(None),
+        };
+        self.pos += 1;
+        let labels = self.label_list()?;
+
+        let group = match self.peek_ident() {
+            Some(side @ ("group_left" | "group_right")) => {
+                let left = side == "group_left";
+                self.pos += 1;
+                let labels = if self.peek() == Some(&TokenKind::LParen) {
+                    self.label_list()?
+                } else {
+                    Vec::new()
+                };
+                Some(if left {
+                    Group::Left(labels)
+                } else {
+                    Group::Right(labels)
+                })
+            }
+            _ => None,
+        };
+        Ok(Some(VectorMatching { on, labels, group }))
+    }
+
+    fn unary(&mut self) -> Result<Expr, ParseError> {
+        let start = self.next_span().start;
+        match self.peek() {
+            Some(TokenKind::LBrace) => Ok(Expr::Log(self.log_expr()?)),
+            Some(TokenKind::LParen) => {
+                self.pos += 1;
+                let expr = self.expr(0)?;
+                self.expect(&TokenKind::RParen, "\")\"")?;
+                Ok(match expr {
+                    Expr::Metric(metric) => Expr::Metric(MetricExpr::Parens(Box::new(metric))),
+                    log => log,
+                })
+            }
+            Some(TokenKind::Number(_) | TokenKind::Sub) => {
+                let value = self.number()?;
+                Ok(Expr::Metric(MetricExpr::Number {
+                    value,
+                    span: start..self.last_end(),
+                }))
+            }
+            Some(TokenKind::Ident(ident)) => {
+                let ident = ident.clone();
+                if RANGE_AGGREGATIONS.contains(&ident.as_str()) {
+                    self.range_aggregation()
+                } else if VECTOR_AGGREGATIONS.contains(&ident.as_str()) {
+                    self.vector_aggregation()
+                } else if ident == "vector" {
+                    self.pos += 1;
+                    self.expect(&TokenKind::LParen, "\"(\"")?;
+                    let value = self.number()?;
+                    self.expect(&TokenKind::RParen, "\")\"")?;
+                    Ok(Expr::Metric(MetricExpr::Vector {
+                        value,
+                        span: start..self.last_end(),
+                    }))
+                } else if ident == "label_replace" {
+                    self.label_replace()
+                } else {
+                    Err(ParseError::new(
+                        format!("unknown function: {ident}"),
+                        self.next_span(),
+                    ))
+                }
+            }
+            _ => self.error("a stream selector, number or function"),
+        }
+    }
+
+    fn metric(&mut self) -> Result<MetricExpr, ParseError> {
+        let span = self.next_span();
+        match self.expr(0)? {
+            Expr::Metric(metric) => Ok(metric),
+            Expr::Log(_) => Err(ParseError::new(
+                String::from("expected a metric query, wrap log queries in a range aggregation like count_over_time"),
+                span.start..self.last_end(),
+            )),
+        }
+    }
+
+    fn range_aggregation(&mut self) -> Result<Expr, ParseError> {
+        let start = self.next_span().start;
+        let function = self.ident("a range aggregation")?;
+        self.expect(&TokenKind::LParen, "\"(\"")?;
+
+        let param = if function == "quantile_over_time" {
+            let param = self.number()?;
+            self.expect(&TokenKind::Comma, "\",\"")?;
+            Some(param)
+        } else {
+            None
+        };
+
+        let log_start = self.next_span().start;
+        let selector = self.selector()?;
+        // The range can follow the selector or the whole pipeline
+        let mut range = None;
+        if self.eat(&TokenKind::LBracket) {
+            range = Some(self.duration()?);
+            self.expect(&TokenKind::RBracket, "\"]\"")?;
+        }
+        let pipeline = self.pipeline()?;
+        let log = LogExpr {
+            selector,
+            pipeline,
+            span: log_start..self.last_end(),
+        };
+        let range = match range {
+            Some(range) => range,
+            None => {
+                self.expect(&TokenKind::LBracket, "a range like [5m]")?;
+                let range = self.duration()?;
+                self.expect(&TokenKind::RBracket, "\"]\"")?;
+                range
+            }
+        };
+        let offset = if self.eat_keyword("offset") {
+            Some(self.duration()?)
+        } else {
+            None
+        };
+        self.expect(&TokenKind::RParen, "\")\"")?;
+        let grouping = self.grouping()?;
+
+        Ok(Expr::Metric(MetricExpr::RangeAggregation {
+            function,
+            param,
+            log,
+            range,
+            offset,
+            grouping,
+            span: start..self.last_end(),
+        }))
+    }
+
+    fn vector_aggregation(&mut self) -> Result<Expr, ParseError> {
+        let start = self.next_span().start;
+        let operation = self.ident("a vector aggregation")?;
+        let mut grouping = self.grouping()?;
+        self.expect(&TokenKind::LParen, "\"(\"")?;
+
+        let param = if matches!(operation.as_str(), "topk" | "bottomk" | "approx_topk") {
+            let param = self.number()?;
+            self.expect(&TokenKind::Comma, "\",\"")?;
+            Some(param)
+        } else {
+            None
+        };
+        let expr = self.metric()?;
+        self.expect(&TokenKind::RParen, "\")\"")?;
+        if grouping.is_none() {
+            grouping = self.grouping()?;
+        }
+
+        Ok(Expr::Metric(MetricExpr::VectorAggregation {
+            operation,
+            param,
+            expr: Box::new(expr),
+            grouping,
+            span: start..self.last_end(),
+        }))
+    }
+
+    fn label_replace(&mut self) -> Result<Expr, ParseError> {
+        let start = self.next_span().start;
+        self.pos += 1;
+        self.expect(&TokenKind::LParen, "\"(\"")?;
+        let expr = self.metric()?;
+        let mut strings = Vec::new();
+        for _ in 0..4 {
+            self.expect(&TokenKind::Comma, "\",\"")?;
+            strings.push(self.string("a string")?);
+        }
+        self.expect(&TokenKind::RParen, "\")\"")?;
+        let [destination, replacement, source, regex] = <[String; 4]>::try_from(strings).unwrap();
+
+        Ok(Expr::Metric(MetricExpr::LabelReplace {
+            expr: Box::new(expr),
+            destination,
+            replacement,
+            source,
+            regex,
+            span: start..self.last_end(),
+        }))
+    }
+
+    fn grouping(&mut self) -> Result<Option<Grouping>, ParseError> {
+        let without = match self.peek_ident() {
+            Some("by") => false,
+            Some("without") => true,
+            _ => return Ok(None),
+        };
+        self.pos += 1;
+        let labels = self.label_list()?;
+        Ok(Some(Grouping { without, labels }))
+    }
+
+    /// `(a, b, c)`
+    fn label_list(&mut self) -> Result<Vec<String>, ParseError> {
+        self.expect(&TokenKind::LParen, "\"(\"")?;
+        let mut labels = Vec::new();
+        while !self.eat(&TokenKind::RParen) {
+            if !labels.is_empty() {
+                self.expect(&TokenKind::Comma, "\",\" or \")\"")?;
+            }
+            labels.push(self.ident("a label name")?);
+        }
+        Ok(labels)
+    }
+
+    fn log_expr(&mut self) -> Result<LogExpr, ParseError> {
+        let start = self.next_span().start;
+        let selector = self.selector()?;
+        let pipeline = self.pipeline()?;
+        Ok(LogExpr {
+            selector,
+            pipeline,
+            span: start..self.last_end(),
+        })
+    }
+
+    fn selector(&mut self) -> Result<Selector, ParseError> {
+        let start = self.expect(&TokenKind::LBrace, "a stream selector like {job=\"a\"}")?;
+        let mut matchers = Vec::new();
+        while !self.eat(&TokenKind::RBrace) {
+            if !matchers.is_empty() {
+                self.expect(&TokenKind::Comma, "\",\" or \"}\"")?;
+                // Allow a trailing comma
+                if self.eat(&TokenKind::RBrace) {
+                    break;
+                }
+            }
+            matchers.push(self.matcher()?);
+        }
+        if matchers.is_empty() {
+            return Err(ParseError::new(
+                String::from("stream selectors need at least one matcher"),
+                start.start..self.last_end(),
+            ));
+        }
+        Ok(Selector {
+            matchers,
+            span: start.start..self.last_end(),
+        })
+    }
+
+    fn matcher(&mut self) -> Result<Matcher, ParseError> {
+        let start = self.next_span().start;
+        let name = self.ident("a label name")?;
+        let op = self.match_op()?;
+        let value = self.string("a quoted value")?;
+        Ok(Matcher {
+            name,
+            op,
+            value,
+            span: start..self.last_end(),
+        })
+    }
+
+    fn match_op(&mut self) -> Result<MatchOp, ParseError> {
+        let op = match self.peek() {
+            Some(TokenKind::Eq) => MatchOp::Eq,
+            Some(TokenKind::Neq) => MatchOp::Neq,
+            Some(TokenKind::Re) => MatchOp::Re,
+            Some(TokenKind::Nre) => MatchOp::Nre,
+            _ => return self.error("one of =, !=, =~, !~"),
+        };
+        self.pos += 1;
+        Ok(op)
+    }
+
+    fn pipeline(&mut self) -> Result<Vec<Stage>, ParseError> {
+        let mut stages = Vec::new();
+        loop {
+            let start = self.next_span().start;
+            let line_filter = match self.peek() {
+                Some(TokenKind::PipeExact) => LineFilterOp::Contains,
+                Some(TokenKind::Neq) => LineFilterOp::NotContains,
+                Some(TokenKind::PipeMatch) => LineFilterOp::Match,
+                Some(TokenKind::Nre) => LineFilterOp::NotMatch,
+                Some(TokenKind::PipePattern) => LineFilterOp::Pattern,
+                Some(TokenKind::NotPattern) => LineFilterOp::NotPattern,
+                Some(TokenKind::Pipe) => {
+                    self.pos += 1;
+                    stages.push(self.stage(start)?);
+                    continue;
+                }
+                _ => return Ok(stages),
+            };
+            self.pos += 1;
+
+            let mut values = vec![self.line_filter_value()?];
+            while self.eat_keyword("or") {
+                values.push(self.line_filter_value()?);
+            }
+            stages.push(Stage::LineFilter {
+                op: line_filter,
+                values,
+                span: start..self.last_end(),
+            });
+        }
+    }
+
+    fn line_filter_value(&mut self) -> Result<LineFilterValue, ParseError> {
+        if self.eat_keyword("ip") {
+            self.expect(&TokenKind::LParen, "\"(\"")?;
+            let ip = self.string("an ip address or range")?;
+            self.expect(&TokenKind::RParen, "\")\"")?;
+            return Ok(LineFilterValue::Ip(ip));
+        }
+        Ok(LineFilterValue::String(self.string("a quoted string")?))
+    }
+
+    /// Everything that can follow a `|`
+    fn stage(&mut self, start: usize) -> Result<Stage, ParseError> {
+        let parser = match self.peek_ident() {
+            Some("json") => Some(ParserKind::Json),
+            Some("logfmt") => Some(ParserKind::Logfmt),
+            Some("regexp") => Some(ParserKind::Regexp),
+            Some("pattern") => Some(ParserKind::Pattern),
+            Some("unpack") => Some(ParserKind::Unpack),
+            _ => None,
+        };
+        if let Some(parser) = parser {
+            self.pos += 1;
+            let mut flags = Vec::new();
+            let mut params = Vec::new();
+            match parser {
+                ParserKind::Regexp | ParserKind::Pattern => {
+                    params.push(LabelExtraction {
+                        name: String::new(),
+                        value: Some(LabelExtractionValue::String(
+                            self.string("a quoted expression")?,
+                        )),
+                    });
+                }
+                ParserKind::Json | ParserKind::Logfmt => {
+                    while let Some(TokenKind::Flag(flag)) = self.peek() {
+                        flags.push(flag.clone());
+                        self.pos += 1;
+                    }
+                    if self.peek_ident().is_some() && !self.is_keyword_ahead() {
+                        params = self.label_extractions(false)?;
+                    }
+                }
+                ParserKind::Unpack => {}
+            }
+            return Ok(Stage::Parser {
+                parser,
+                flags,
+                params,
+                span: start..self.last_end(),
+            });
+        }
+
+        match self.peek_ident() {
+            Some("line_format") => {
+                self.pos += 1;
+                let template = self.string("a quoted template")?;
+                Ok(Stage::LineFormat {
+                    template,
+                    span: start..self.last_end(),
+                })
+            }
+            Some("label_format") => {
+                self.pos += 1;
+                let labels = self.label_extractions(true)?;
+                Ok(Stage::LabelFormat {
+                    labels,
+                    span: start..self.last_end(),
+                })
+            }
+            Some(keyword @ ("drop" | "keep")) => {
+                let drop = keyword == "drop";
+                self.pos += 1;
+                let mut labels = Vec::new();
+                loop {
+                    let matcher_start = self.next_span().start;
+                    let name = self.ident("a label name")?;
+                    let matcher = match self.peek() {
+                        Some(TokenKind::Eq | TokenKind::Neq | TokenKind::Re | TokenKind::Nre) => {
+                            let op = self.match_op()?;
+                            let value = self.string("a quoted value")?;
+                            Some(Matcher {
+                                name: name.clone(),
+                                op,
+                                value,
+                                span: matcher_start..self.last_end(),
+                            })
+                        }
+                        _ => None,
+                    };
+                    labels.push(LabelSelection { name, matcher });
+                    if !self.eat(&TokenKind::Comma) {
+                        break;
+                    }
+                }
+                let span = start..self.last_end();
+                Ok(if drop {
+                    Stage::Drop { labels, span }
+                } else {
+                    Stage::Keep { labels, span }
+                })
+            }
+            Some("decolorize") => {
+                self.pos += 1;
+                Ok(Stage::Decolorize {
+                    span: start..self.last_end(),
+                })
+            }
+            Some("unwrap") => {
+                self.pos += 1;
+                let name = self.ident("a label name")?;
+                let (label, conversion) = if UNWRAP_CONVERSIONS.contains(&name.as_str())
+                    && self.eat(&TokenKind::LParen)
+                {
+                    let label = self.ident("a label name")?;
+                    self.expect(&TokenKind::RParen, "\")\"")?;
+                    (label, Some(name))
+                } else {
+                    (name, None)
+                };
+                Ok(Stage::Unwrap {
+                    label,
+                    conversion,
+                    span: start..self.last_end(),
+                })
+            }
+            Some(_) => {
+                let filter = self.label_filter_or()?;
+                Ok(Stage::LabelFilter {
+                    filter,
+                    span: start..self.last_end(),
+                })
+            }
+            // A label filter in parentheses, like `(level="error" or level="warn")`
+            None if self.peek() == Some(&TokenKind::LParen) => {
+                let filter = self.label_filter_or()?;
+                Ok(Stage::LabelFilter {
+                    filter,
+                    span: start..self.last_end(),
+                })
+            }
+            None => self.error("a parser, formatter or label filter"),
+        }
+    }
+
+    /// True if the identifier ahead starts something other than a parser parameter,
+    /// like the `or`/`and`/`by` in `count_over_time({a="b"} | json [5m]) by (c)`
+    fn is_keyword_ahead(&self) -> bool {
+        matches!(
+            self.peek_ident(),
+            Some("or" | "and" | "unless" | "by" | "without" | "offset")
+        ) && !matches!(
+            self.tokens.get(self.pos + 1).map(|token| &token.kind),
+            Some(TokenKind::Eq | TokenKind::Comma)
+        )
+    }
+
+    /// `a, b="c"` for json and logfmt, `a=b, c="{{.d}}"` for label_format
+    fn label_extractions(
+        &mut self,
+        require_value: bool,
+    ) -> Result<Vec<LabelExtraction>, ParseError> {
+        let mut extractions = Vec::new();
+        loop {
+            let name = self.ident("a label name")?;
+            let value = if self.eat(&TokenKind::Eq) {
+                match self.peek() {
+                    Some(TokenKind::String(string)) => {
+                        let string = string.clone();
+                        self.pos += 1;
+                        Some(LabelExtractionValue::String(string))
+                    }
+                    Some(TokenKind::Ident(ident)) if require_value => {
+                        let ident = ident.clone();
+                        self.pos += 1;
+                        Some(LabelExtractionValue::Label(ident))
+                    }
+                    _ => return self.error("a quoted expression"),
+                }
+            } else if require_value {
+                return self.error("\"=\"");
+            } else {
+                None
+            };
+            extractions.push(LabelExtraction { name, value });
+            if !self.eat(&TokenKind::Comma) {
+                return Ok(extractions);
+            }
+        }
+    }
+
+    fn label_filter_or(&mut self) -> Result<LabelFilter, ParseError> {
+        let mut left = self.label_filter_and()?;
+        while self.eat_keyword("or") {
+            let right = self.label_filter_and()?;
+            left = LabelFilter::Or(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn label_filter_and(&mut self) -> Result<LabelFilter, ParseError> {
+        let mut left = self.label_filter_primary()?;
+        while self.eat_keyword("and") || self.eat(&TokenKind::Comma) {
+            let right = self.label_filter_primary()?;
+            left = LabelFilter::And(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn label_filter_primary(&mut self) -> Result<LabelFilter, ParseError> {
+        if self.eat(&TokenKind::LParen) {
+            let filter = self.label_filter_or()?;
+            self.expect(&TokenKind::RParen, "\")\"")?;
+            return Ok(filter);
+        }
+
+        let start = self.next_span().start;
+        let label = self.ident("a label name")?;
+        let op = match self.peek() {
+            Some(TokenKind::Eq) => CompareOp::Eq,
+            Some(TokenKind::EqEq) => CompareOp::Eq,
+            Some(TokenKind::Neq) => CompareOp::Neq,
+            Some(TokenKind::Gt) => CompareOp::Gt,
+            Some(TokenKind::Ge) => CompareOp::Ge,
+            Some(TokenKind::Lt) => CompareOp::Lt,
+            Some(TokenKind::Le) => CompareOp::Le,
+            Some(TokenKind::Re | TokenKind::Nre) => {
+                let op = self.match_op()?;
+                let value = self.string("a quoted regex")?;
+                return Ok(LabelFilter::Matcher(Matcher {
+                    name: label,
+                    op,
+                    value,
+                    span: start..self.last_end(),
+                }));
+            }
+            _ => return self.error("a comparison operator"),
+        };
+        let op_token = self.bump().unwrap();
+
+        let value = match self.peek() {
+            Some(TokenKind::String(value))
+                if matches!(op_token.kind, TokenKind::Eq | TokenKind::Neq) =>
+            {
+                let value = value.clone();
+                self.pos += 1;
+                return Ok(LabelFilter::Matcher(Matcher {
+                    name: label,
+                    op: if op == CompareOp::Eq {
+                        MatchOp::Eq
+                    } else {
+                        MatchOp::Neq
+                    },
+                    value,
+                    span: start..self.last_end(),
+                }));
+            }
+            Some(TokenKind::Ident(ip)) if ip == "ip" && op_token.kind != TokenKind::EqEq => {
+                if !matches!(op, CompareOp::Eq | CompareOp::Neq) {
+                    return Err(ParseError::new(
+                        String::from("ip filters only support = and !="),
+                        op_token.span,
+                    ));
+                }
+                self.pos += 1;
+                self.expect(&TokenKind::LParen, "\"(\"")?;
+                let value = self.string("an ip address or range")?;
+                self.expect(&TokenKind::RParen, "\")\"")?;
+                return Ok(LabelFilter::Ip {
+                    label,
+                    negated: op == CompareOp::Neq,
+                    value,
+                });
+            }
+            Some(TokenKind::Number(_) | TokenKind::Sub) => Literal::Number(self.number()?),
+            Some(TokenKind::Duration(duration)) => {
+                let duration = *duration;
+                self.pos += 1;
+                Literal::Duration(duration)
+            }
+            Some(TokenKind::Bytes(bytes)) => {
+                let bytes = *bytes;
+                self.pos += 1;
+                Literal::Bytes(bytes)
+            }
+            _ => return self.error("a string, number, duration or byte size"),
+        };
+        Ok(LabelFilter::Compare { label, op, value })
+    }
+}
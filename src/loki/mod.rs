@@ -1,21 +1,53 @@
 use std::{collections::HashMap, fmt::Display, io::Cursor, ops::Add};
 
-use chrono::{DateTime, Duration, Local, NaiveDateTime};
-use log::error;
-use loki_api::{
-    logproto::{EntryAdapter, PushRequest, StreamAdapter},
-    prost,
-    prost_types::Timestamp,
-};
-mod error;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{DateTime, Duration, Local, SecondsFormat};
+/// A local record of the deletes and pushes sent to Loki
+pub mod audit;
+/// Listing and cancelling delete requests
+pub mod delete;
+/// The errors of all requests to Loki
+pub mod error;
+/// Validated label sets and the escaping of label values
+pub mod labels;
+/// Parsing LogQL queries locally
+pub mod logql;
+/// Reading log queries past the limit of a single request
+pub mod paging;
+/// The protobuf messages of the push api, with the structured metadata `loki_api` lacks
+pub mod proto;
+/// Pushing log lines, one request at a time or batched
+pub mod push;
+/// The typed results of queries
+pub mod result;
+/// Streaming new log lines over a websocket
+pub mod tail;
+/// Absolute and relative times for query ranges
+pub mod time;
 /// The json types used in rest requests
 pub mod types;
 
-use serde_json::{Map, Value};
-use types::LokiLabels;
+use audit::{os_user, AuditLog, AuditRecord, Operation};
+use delete::DeleteRequest;
+use labels::Labels;
+use log::error;
+use paging::{Next, PagedResult, Pages};
+use proto::{PushRequest, StreamAdapter};
+use push::{PushEntry, PushStream};
+use result::LokiResult;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use tail::Tail;
+use time::to_nanos;
+use tungstenite::client::IntoClientRequest;
+use types::{LokiLabels, LokiSeriesList};
 
 use self::error::Error;
 
+/// Asks Loki 3 to return structured metadata and parsed labels apart from the stream labels
+const ENCODING_FLAGS: &str = "X-Loki-Response-Encoding-Flags";
+const CATEGORIZE_LABELS: &str = "categorize-labels";
+
 ///
 /// A buffer that can be used to encode and compress protobuf messages.
 /// This is then used by communications with Loki.
@@ -57,72 +89,11 @@ impl Buffer {
     }
 }
 
-#[derive(Debug)]
-pub struct LokiResult {
-    pub labels: HashMap<String, String>,
-    pub values: Vec<LokiValue>,
-}
-
-impl LokiResult {
-    fn from_json(mut labels: Map<String, Value>, values: Vec<LokiValue>) -> Self {
-        let mut labels_new: HashMap<_, _> = HashMap::new();
-        let keys = labels.keys().cloned().collect::<Vec<_>>();
-        for key in keys {
-            let (k, v) = labels.remove_entry(&key).unwrap();
-            labels_new.insert(k, v.as_str().unwrap().to_owned());
-        }
-        Self {
-            labels: labels_new,
-            values,
-        }
-    }
-}
-
-#[derive(Debug)]
-pub struct LokiValue {
-    pub timestamp: DateTime<Local>,
-    pub log_line: String,
-}
-
-impl LokiValue {
-    fn from_nano(timestamp: String, log_line: String) -> Option<Self> {
-        let timestamp = timestamp.parse::<i64>().expect("Unable to parse timestamp");
-        let secs = timestamp / 1_000_000_000;
-        let ns = timestamp - (secs * 1_000_000_000);
-
-        let dt = NaiveDateTime::from_timestamp_opt(secs, ns as u32);
-        //let timestamp = Local.from_local_datetime(&dt.expect("Unable to parse timestamp")).unwrap();
-        let timestamp =
-            DateTime::<Local>::from_naive_utc_and_offset(dt.unwrap(), *Local::now().offset());
-        Some(Self {
-            timestamp,
-            log_line,
-        })
-    }
-
-    fn from_sec(timestamp: i64, log_line: String) -> Option<Self> {
-        let dt = NaiveDateTime::from_timestamp_opt(timestamp, 0);
-        let timestamp =
-            DateTime::<Local>::from_naive_utc_and_offset(dt.unwrap(), *Local::now().offset());
-        Some(Self {
-            timestamp,
-            log_line,
-        })
-    }
-}
-
-impl Display for LokiValue {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "[{}] {}", self.timestamp, self.log_line)
-    }
-}
-
 ///
 /// A very basic Loki client
 /// # Example
 /// ```
 /// use loki_ui::loki::Loki;
-/// use log::error;
 ///
 /// let mut loki = Loki::new(String::from("http://localhost:3100"));
 /// let result = loki.labels(None, None).unwrap();
@@ -135,104 +106,258 @@ impl Display for LokiValue {
 #[derive(Clone)]
 pub struct Loki {
     pub address: String,
+    pub credentials: Credentials,
+    /// Sent as `X-Scope-OrgID` for multi-tenant Loki setups
+    pub tenant_id: Option<String>,
+    /// Where deletes and pushes are recorded, nothing is recorded if `None`
+    pub audit_log: Option<AuditLog>,
+    /// Refuses every request that changes data, like deletes and pushes
+    pub read_only: bool,
     buffer: Buffer,
 }
 
+/// The order log lines are returned in
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Direction {
+    /// Oldest lines first
+    Forward,
+    /// Newest lines first, Loki's default
+    #[default]
+    Backward,
+}
+
+impl Direction {
+    /// The value of the `direction` parameter
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Direction::Forward => "forward",
+            Direction::Backward => "backward",
+        }
+    }
+}
+
+impl Display for Direction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// The optional parameters of a range query
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct QueryOptions {
+    /// The maximum number of log lines, Loki's default if `None`
+    pub limit: Option<i64>,
+    pub direction: Direction,
+    /// The resolution of metric queries, chosen by Loki from the range if `None`
+    pub step: Option<std::time::Duration>,
+    /// Only returns log lines this far apart, only applies to log queries
+    pub interval: Option<std::time::Duration>,
+}
+
+/// The credentials that are sent with every request
+#[derive(Clone, Debug, Default)]
+pub enum Credentials {
+    #[default]
+    None,
+    Basic {
+        username: String,
+        password: String,
+    },
+    Bearer(String),
+}
+
+impl Credentials {
+    /// The value of the `Authorization` header for these credentials
+    pub fn authorization(&self) -> Option<String> {
+        match self {
+            Credentials::None => None,
+            Credentials::Basic { username, password } => Some(format!(
+                "Basic {}",
+                STANDARD.encode(format!("{username}:{password}"))
+            )),
+            Credentials::Bearer(token) => Some(format!("Bearer {token}")),
+        }
+    }
+}
+
 impl Loki {
     /// Create a new Loki client with a given address
     #[must_use]
     pub fn new(address: String) -> Self {
         Self {
             address,
+            credentials: Credentials::None,
+            tenant_id: None,
+            audit_log: None,
+            read_only: false,
             buffer: Buffer::new(),
         }
     }
 
+    /// Creates a request to the given api path with the auth and tenant headers set
+    fn request(&self, method: &str, path: &str) -> ureq::Request {
+        let mut request = ureq::request(method, &format!("{}{}", self.address, path));
+        if let Some(authorization) = self.credentials.authorization() {
+            request = request.set("Authorization", &authorization);
+        }
+        if let Some(tenant_id) = &self.tenant_id {
+            request = request.set("X-Scope-OrgID", tenant_id);
+        }
+        request
+    }
+
     /// Runs a loki query and returns the results
     pub fn query_range(
         &mut self,
         query: &str,
-        limit: Option<i64>,
+        options: &QueryOptions,
+        start: Option<DateTime<Local>>,
+        end: Option<DateTime<Local>>,
+    ) -> Result<LokiResult, Error> {
+        let (start, end) = request_range(start, end);
+        let limit = options.limit.unwrap_or(100).to_string();
+        let mut pairs = vec![
+            ("start", start),
+            ("end", end),
+            ("limit", limit),
+            ("direction", options.direction.to_string()),
+        ];
+        // Loki takes both as a duration or a float number of seconds
+        if let Some(step) = options.step {
+            pairs.push(("step", step.as_secs_f64().to_string()));
+        }
+        if let Some(interval) = options.interval {
+            pairs.push(("interval", interval.as_secs_f64().to_string()));
+        }
+
+        let response = self
+            .request("GET", "/loki/api/v1/query_range")
+            .set(ENCODING_FLAGS, CATEGORIZE_LABELS)
+            .query_pairs(pairs.iter().map(|(key, value)| (*key, value.as_str())))
+            .query("query", query)
+            .call()?;
+
+        let text: Value = json(response, "query results")?;
+        LokiResult::from_json(&text)
+    }
+
+    ///
+    /// Runs a log query page by page, until the range is exhausted or `max_lines` lines were read.
+    /// Each page has the options' limit (1000 by default) and starts where the last one ended.
+    /// Metric queries are not paged and return their result as is.
+    pub fn query_range_paged(
+        &mut self,
+        query: &str,
+        options: &QueryOptions,
         start: Option<DateTime<Local>>,
         end: Option<DateTime<Local>>,
-    ) -> Result<Vec<LokiResult>, Error> {
-        let start = start
-            .unwrap_or(Local::now().add(Duration::hours(-6)))
-            .timestamp()
-            .to_string();
-        let end = end.unwrap_or(Local::now()).timestamp().to_string();
+        max_lines: usize,
+    ) -> Result<PagedResult, Error> {
+        let limit = options.limit.unwrap_or(1000).max(1);
+        let options = QueryOptions {
+            limit: Some(limit),
+            ..options.clone()
+        };
+        let now = Local::now();
+        let mut start = start.unwrap_or(now.add(Duration::hours(-6)));
+        let mut end = end.unwrap_or(now);
+        let mut pages = Pages::new(options.direction, max_lines);
+
+        loop {
+            let streams = match self.query_range(query, &options, Some(start), Some(end))? {
+                LokiResult::Streams(streams) => streams,
+                result => {
+                    return Ok(PagedResult {
+                        result,
+                        lines: 0,
+                        truncated: false,
+                    })
+                }
+            };
+            match pages.add(streams, limit as usize) {
+                Next::Page(boundary) => match options.direction {
+                    Direction::Backward => end = boundary,
+                    Direction::Forward => start = boundary,
+                },
+                Next::Done => break,
+            }
+            if start >= end {
+                break;
+            }
+        }
+        Ok(pages.finish())
+    }
+
+    /// Runs an instant loki query at a single point in time and returns the results
+    pub fn query(
+        &mut self,
+        query: &str,
+        limit: Option<i64>,
+        time: Option<DateTime<Local>>,
+    ) -> Result<LokiResult, Error> {
+        let time = to_nanos(&time.unwrap_or(Local::now()));
         let limit = limit.unwrap_or(100);
 
-        let response = ureq::get(&format!("{}/loki/api/v1/query_range", self.address))
+        let response = self
+            .request("GET", "/loki/api/v1/query")
+            .set(ENCODING_FLAGS, CATEGORIZE_LABELS)
             .query_pairs(vec![
-                ("start", start.as_str()),
-                ("end", end.as_str()),
+                ("time", time.as_str()),
                 ("limit", limit.to_string().as_str()),
             ])
             .query("query", query)
-            .call();
+            .call()?;
 
-        if let Err(e) = response {
-            return Err(Error::with_source(
-                Box::new(e),
-                String::from("Error receiving data from Loki"),
-            ));
-        }
+        let text: Value = json(response, "query results")?;
+        LokiResult::from_json(&text)
+    }
 
-        let response = response.unwrap();
-        if response.status() != 200 {
-            return Err(Error::new(format!(
-                "Error sending data to Loki: {:?}",
-                response.into_string()
+    /// Opens a websocket that receives new log lines for the query as they arrive.
+    /// `delay_for` is the number of seconds (at most 5) Loki waits to collect late lines.
+    pub fn tail(
+        &self,
+        query: &str,
+        delay_for: Option<u32>,
+        limit: Option<i64>,
+        start: Option<DateTime<Local>>,
+    ) -> Result<Tail, Error> {
+        let mut url = url::Url::parse(&format!("{}/loki/api/v1/tail", self.address))
+            .map_err(|e| Error::InvalidInput(format!("Invalid Loki URL {}: {e}", self.address)))?;
+        let scheme = if url.scheme() == "https" { "wss" } else { "ws" };
+        // Only fails for schemes that cannot be switched, like "file"
+        if url.set_scheme(scheme).is_err() {
+            return Err(Error::InvalidInput(format!(
+                "Unsupported Loki URL: {}",
+                self.address
             )));
         }
-        let text = &response.into_string().unwrap();
-        let text: Value = serde_json::from_str(text).unwrap();
-
-        // There are two different types of results in loki
-        // matrix and streams. We can read /data/resultType to find out
-        let result_type = text.pointer("/data/resultType").unwrap();
-        let mut results = Vec::new();
-        if result_type == "streams" {
-            let streams = text.pointer("/data/result").unwrap().as_array().unwrap();
-            for stream in streams {
-                let labels: Map<String, Value> = stream["stream"].as_object().unwrap().clone();
-
-                let values = stream["values"].as_array().unwrap();
-                let mut values_vec = Vec::new();
-                for value in values {
-                    let timestamp = value[0].as_str().unwrap().to_owned();
-                    let log_line = value[1].as_str().unwrap().to_owned();
-                    let result = LokiValue::from_nano(timestamp, log_line);
-                    if let Some(result) = result {
-                        values_vec.push(result);
-                    }
-                }
+        url.query_pairs_mut()
+            .append_pair("query", query)
+            .append_pair("delay_for", &delay_for.unwrap_or(0).to_string())
+            .append_pair("limit", &limit.unwrap_or(100).to_string());
+        if let Some(start) = start {
+            url.query_pairs_mut()
+                .append_pair("start", &to_nanos(&start));
+        }
 
-                results.push(LokiResult::from_json(labels, values_vec));
+        let mut request = url
+            .as_str()
+            .into_client_request()
+            .map_err(|e| Error::InvalidInput(format!("Invalid tail request: {e}")))?;
+        let headers = request.headers_mut();
+        if let Some(authorization) = self.credentials.authorization() {
+            if let Ok(value) = authorization.parse() {
+                headers.insert("Authorization", value);
             }
-        } else if result_type == "matrix" {
-            let streams = text.pointer("/data/result").unwrap().as_array().unwrap();
-            for stream in streams {
-                let labels: Map<String, Value> = stream["metric"].as_object().unwrap().clone();
-
-                let values = stream["values"].as_array().unwrap();
-                let mut values_vec = Vec::new();
-                for value in values {
-                    let timestamp = value[0].as_i64().unwrap().to_owned();
-                    let log_line = value[1].as_str().unwrap().to_owned();
-                    let result = LokiValue::from_sec(timestamp, log_line);
-                    if let Some(result) = result {
-                        values_vec.push(result);
-                    }
-                }
-
-                results.push(LokiResult::from_json(labels, values_vec));
+        }
+        if let Some(tenant_id) = &self.tenant_id {
+            if let Ok(value) = tenant_id.parse() {
+                headers.insert("X-Scope-OrgID", value);
             }
-        } else {
-            error!("Unknown result type: {result_type}");
         }
-        Ok(results)
+
+        let (socket, _) = tungstenite::connect(request)?;
+        Ok(Tail::new(socket))
     }
 
     /// Retrieve the values for a given label from Loki
@@ -242,41 +367,17 @@ impl Loki {
         start: Option<DateTime<Local>>,
         end: Option<DateTime<Local>>,
         query: Option<&str>,
-    ) -> Option<Vec<String>> {
-        let start = start
-            .unwrap_or(Local::now().add(Duration::hours(-6)))
-            .timestamp()
-            .to_string();
-        let end = end.unwrap_or(Local::now()).timestamp().to_string();
-
-        let response = ureq::get(&format!(
-            "{}/loki/api/v1/label/{}/values",
-            self.address, label
-        ))
-        .query_pairs(vec![("start", start.as_str()), ("end", end.as_str())])
-        .query("query", query.unwrap_or(""))
-        .call();
-
-        if let Err(e) = response {
-            error!("Error receiving label values: {e}");
-            return None;
-        }
-
-        let response = response.unwrap();
-        if response.status() != 200 {
-            error!("Error sending data to Loki: {}", response.status());
-            error!("Response: {:?}", response.into_string());
-            return None;
-        }
+    ) -> Result<Vec<String>, Error> {
+        let (start, end) = request_range(start, end);
 
-        let text = response.into_json::<LokiLabels>();
-        if let Err(e) = text {
-            error!("Error parsing labels: {e}");
-            return None;
-        }
+        let response = self
+            .request("GET", &format!("/loki/api/v1/label/{}/values", label))
+            .query_pairs(vec![("start", start.as_str()), ("end", end.as_str())])
+            .query("query", query.unwrap_or(""))
+            .call()?;
 
-        let labels_response = text.unwrap();
-        Some(labels_response.data)
+        let values: LokiLabels = json(response, "label values")?;
+        Ok(values.data)
     }
 
     /// Retrieve the labels from Loki
@@ -284,55 +385,51 @@ impl Loki {
         &mut self,
         start: Option<DateTime<Local>>,
         end: Option<DateTime<Local>>,
-    ) -> Option<Vec<String>> {
-        let start = start
-            .unwrap_or(Local::now().add(Duration::hours(-6)))
-            .timestamp()
-            .to_string();
-        let end = end.unwrap_or(Local::now()).timestamp().to_string();
-
-        let response = ureq::get(&format!("{}/loki/api/v1/labels", self.address))
-            .query_pairs(vec![("start", start.as_str()), ("end", (end.as_str()))])
-            .call();
+    ) -> Result<Vec<String>, Error> {
+        let (start, end) = request_range(start, end);
 
-        if let Err(e) = response {
-            error!("Error receiving labels: {e}");
-            return None;
-        }
+        let response = self
+            .request("GET", "/loki/api/v1/labels")
+            .query_pairs(vec![("start", start.as_str()), ("end", (end.as_str()))])
+            .call()?;
 
-        let response = response.unwrap();
-        if response.status() != 200 {
-            error!("Error sending data to Loki: {}", response.status());
-            error!("Response: {:?}", response.into_string());
-            return None;
-        }
+        let labels: LokiLabels = json(response, "labels")?;
+        Ok(labels.data)
+    }
 
-        let text = response.into_json::<LokiLabels>();
-        if let Err(e) = text {
-            error!("Error parsing labels: {e}");
-            return None;
+    /// Retrieve the label sets of all streams matching any of the given selectors
+    pub fn series(
+        &mut self,
+        matchers: &[&str],
+        start: Option<DateTime<Local>>,
+        end: Option<DateTime<Local>>,
+    ) -> Result<Vec<HashMap<String, String>>, Error> {
+        let (start, end) = request_range(start, end);
+
+        let mut request = self
+            .request("GET", "/loki/api/v1/series")
+            .query_pairs(vec![("start", start.as_str()), ("end", end.as_str())]);
+        for matcher in matchers {
+            request = request.query("match[]", matcher);
         }
+        let response = request.call()?;
 
-        let labels_response = text.unwrap();
-        Some(labels_response.data)
+        let series: LokiSeriesList = json(response, "series")?;
+        Ok(series.data)
     }
 
     /// Send a message with labels to Loki
-    /// The labels should be in format: `{job="test"}`
-    pub fn send_message(&mut self, line: String, labels: String, time: Option<DateTime<Local>>) {
-        let time = time.unwrap_or(Local::now());
-        let stream_adapter = StreamAdapter {
-            labels,
-            entries: vec![EntryAdapter {
-                timestamp: Some(Timestamp {
-                    seconds: time.timestamp(),
-                    nanos: 0,
-                }),
-                line,
-            }],
-            hash: 0,
-        };
-        self.push(vec![stream_adapter]);
+    pub fn send_message(
+        &mut self,
+        line: String,
+        labels: &Labels,
+        time: Option<DateTime<Local>>,
+    ) -> Result<(), Error> {
+        let entry = PushEntry::new(time.unwrap_or(Local::now()), line);
+        self.push(&[PushStream {
+            labels: labels.clone(),
+            entries: vec![entry],
+        }])
     }
 
     /// Creates a delete request in Loki https://grafana.com/docs/grafana-cloud/send-data/logs/delete-log-lines/
@@ -342,47 +439,156 @@ impl Loki {
         start: Option<DateTime<Local>>,
         end: Option<DateTime<Local>>,
     ) -> Result<(), Error> {
-        let start = start
-            .unwrap_or(Local::now().add(Duration::hours(-6)))
-            .timestamp()
-            .to_string();
-        let end = end.unwrap_or(Local::now()).timestamp().to_string();
-        let response = ureq::post(&format!("{}/loki/api/v1/delete", self.address))
+        self.check_writable("deleting")?;
+        let now = Local::now();
+        let start = start.unwrap_or(now.add(Duration::hours(-6)));
+        let end = end.unwrap_or(now);
+        let result = self.send_delete(query, &start, &end);
+        self.audit(
+            Operation::Delete,
+            query.to_string(),
+            Some(start),
+            Some(end),
+            &result,
+        );
+        result.map(|_| ())
+    }
+
+    /// Sends the delete request and returns the status Loki accepted it with
+    fn send_delete(
+        &mut self,
+        query: &str,
+        start: &DateTime<Local>,
+        end: &DateTime<Local>,
+    ) -> Result<u16, Error> {
+        // The delete api reads integers as seconds, so the times are sent as RFC3339
+        let start = start.to_rfc3339_opts(SecondsFormat::Nanos, true);
+        let end = end.to_rfc3339_opts(SecondsFormat::Nanos, true);
+        let response = self
+            .request("POST", "/loki/api/v1/delete")
             .query_pairs(vec![("start", start.as_str()), ("end", end.as_str())])
             .query("query", query)
-            .call();
+            .call()?;
 
-        if let Err(e) = response {
-            return Err(Error::with_source(
-                Box::new(e),
-                String::from("Error sending delete request"),
+        // Loki accepts delete requests with 204 No Content
+        if response.status() != 204 {
+            return Err(Error::from_status(
+                response.status(),
+                response.into_string().unwrap_or_default(),
             ));
         }
+        Ok(response.status())
+    }
 
-        let response = response.unwrap();
-        if response.status() != 204 {
-            return Err(Error::new(format!(
-                "Delete request was not processed correctly: {:?}",
-                response
+    /// Lists all delete requests with their status, the cancelled ones are not included
+    pub fn delete_requests(&mut self) -> Result<Vec<DeleteRequest>, Error> {
+        let response = self.request("GET", "/loki/api/v1/delete").call()?;
+        let text: Value = json(response, "delete requests")?;
+        DeleteRequest::list_from_json(&text)
+    }
+
+    /// Cancels a delete request, which is only possible before its grace period ended
+    pub fn cancel_delete(&mut self, request_id: &str) -> Result<(), Error> {
+        self.check_writable("cancelling deletes")?;
+        if request_id.is_empty() {
+            return Err(Error::InvalidInput(String::from(
+                "The id of the delete request is missing",
             )));
         }
+        self.request("DELETE", "/loki/api/v1/delete")
+            .query("request_id", request_id)
+            .call()?;
         Ok(())
     }
 
-    fn push(&mut self, streams: Vec<StreamAdapter>) {
+    ///
+    /// Sends log lines to Loki in a single request.
+    /// Fails with the status and body Loki answered with if they were not accepted.
+    pub fn push(&mut self, streams: &[PushStream]) -> Result<(), Error> {
+        self.check_writable("pushing")?;
+        if streams.iter().any(|stream| stream.labels.is_empty()) {
+            return Err(Error::InvalidInput(String::from(
+                "Every stream needs at least one label",
+            )));
+        }
+        let result = self.send_push(streams);
+
+        let labels: Vec<String> = streams.iter().map(|s| s.labels.to_string()).collect();
+        let timestamps = streams
+            .iter()
+            .flat_map(|stream| stream.entries.iter().map(|entry| entry.timestamp));
+        self.audit(
+            Operation::Push,
+            labels.join(", "),
+            timestamps.clone().min(),
+            timestamps.max(),
+            &result,
+        );
+        result.map(|_| ())
+    }
+
+    /// Sends the streams and returns the status Loki accepted them with
+    fn send_push(&mut self, streams: &[PushStream]) -> Result<u16, Error> {
+        let streams = streams.iter().map(StreamAdapter::from).collect();
         let body = &mut self.buffer.encode(&PushRequest { streams }).to_owned();
-        let response = ureq::post(&format!("{}/loki/api/v1/push", self.address))
+        let response = self
+            .request("POST", "/loki/api/v1/push")
             .set("Content-Type", "application/x-snappy")
-            .send(Cursor::new(body));
-
-        if let Err(e) = response {
-            error!("Error sending data to Loki: {e}");
-        } else {
-            let response = response.unwrap();
-            if response.status() != 204 {
-                error!("Error sending data to Loki: {}", response.status());
-                error!("Response: {:?}", response.into_string().unwrap());
-            }
+            .send(Cursor::new(body))?;
+        Ok(response.status())
+    }
+
+    /// Fails with `Error::ReadOnly` for requests that would change data in a read-only client
+    fn check_writable(&self, operation: &'static str) -> Result<(), Error> {
+        match self.read_only {
+            true => Err(Error::ReadOnly(operation)),
+            false => Ok(()),
         }
     }
+
+    /// Appends a record of the request to the audit log, if there is one
+    fn audit(
+        &self,
+        operation: Operation,
+        query: String,
+        start: Option<DateTime<Local>>,
+        end: Option<DateTime<Local>>,
+        result: &Result<u16, Error>,
+    ) {
+        let Some(audit_log) = &self.audit_log else {
+            return;
+        };
+        let record = AuditRecord {
+            timestamp: Local::now(),
+            user: os_user(),
+            url: self.address.clone(),
+            tenant: self.tenant_id.clone(),
+            operation,
+            query,
+            start,
+            end,
+            response: match result {
+                Ok(status) => format!("accepted with status {status}"),
+                Err(error) => error.to_string(),
+            },
+        };
+        if let Err(e) = audit_log.append(&record) {
+            error!("Unable to write the audit log: {e}");
+        }
+    }
+}
+
+/// Reads the json body of a successful response
+fn json<T: DeserializeOwned>(response: ureq::Response, what: &str) -> Result<T, Error> {
+    response
+        .into_json()
+        .map_err(|e| Error::decode_with(e, format!("Error parsing {what} from Loki")))
+}
+
+/// The start and end of a request, the last 6 hours by default
+fn request_range(start: Option<DateTime<Local>>, end: Option<DateTime<Local>>) -> (String, String) {
+    let now = Local::now();
+    let start = start.unwrap_or(now.add(Duration::hours(-6)));
+    let end = end.unwrap_or(now);
+    (to_nanos(&start), to_nanos(&end))
 }
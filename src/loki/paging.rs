@@ -0,0 +1,134 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use chrono::{DateTime, Duration, Local};
+
+use super::{
+    result::{LokiResult, LokiStream, LokiValue},
+    Direction,
+};
+
+/// The log lines of all pages of a query
+#[derive(Debug)]
+pub struct PagedResult {
+    pub result: LokiResult,
+    /// The number of distinct lines in `result`
+    pub lines: usize,
+    /// True if paging stopped at the max lines before the range was exhausted
+    pub truncated: bool,
+}
+
+/// What to request after a page
+#[derive(Debug, PartialEq)]
+pub enum Next {
+    /// Request the next page starting at this time.
+    /// It is the new end when paging backward, and the new start when paging forward.
+    Page(DateTime<Local>),
+    Done,
+}
+
+///
+/// Collects the pages of a query, dropping the lines that appear on both sides of a boundary.
+/// Boundaries include the last seen timestamp, so no lines sharing it get lost.
+pub struct Pages {
+    direction: Direction,
+    max_lines: usize,
+    /// Lines already collected, by their labels, timestamp and text
+    seen: HashSet<(BTreeMap<String, String>, DateTime<Local>, String)>,
+    streams: HashMap<BTreeMap<String, String>, Vec<LokiValue>>,
+    lines: usize,
+    truncated: bool,
+}
+
+impl Pages {
+    pub fn new(direction: Direction, max_lines: usize) -> Self {
+        Self {
+            direction,
+            max_lines,
+            seen: HashSet::new(),
+            streams: HashMap::new(),
+            lines: 0,
+            truncated: false,
+        }
+    }
+
+    /// Adds a page that was requested with `limit` and returns what to request next
+    pub fn add(&mut self, page: Vec<LokiStream>, limit: usize) -> Next {
+        let mut entries: Vec<(BTreeMap<String, String>, LokiValue)> = page
+            .into_iter()
+            .flat_map(|stream| {
+                let labels: BTreeMap<_, _> = stream.labels.into_iter().collect();
+                stream
+                    .values
+                    .into_iter()
+                    .map(move |value| (labels.clone(), value))
+            })
+            .collect();
+        let received = entries.len();
+        // Walk the page away from the boundary, so a truncated result has no gaps
+        entries.sort_by_key(|(_, value)| value.timestamp);
+        if self.direction == Direction::Backward {
+            entries.reverse();
+        }
+        let Some(boundary) = entries.last().map(|(_, value)| value.timestamp) else {
+            return Next::Done;
+        };
+
+        let mut added = 0;
+        for (labels, value) in entries {
+            let key = (labels.clone(), value.timestamp, value.log_line.clone());
+            if self.seen.contains(&key) {
+                continue;
+            }
+            if self.lines >= self.max_lines {
+                self.truncated = true;
+                return Next::Done;
+            }
+            self.seen.insert(key);
+            self.streams.entry(labels).or_default().push(value);
+            self.lines += 1;
+            added += 1;
+        }
+
+        if received < limit {
+            return Next::Done;
+        }
+        if self.lines >= self.max_lines {
+            self.truncated = true;
+            return Next::Done;
+        }
+        // More lines than the limit share the boundary timestamp, step past it to not loop forever
+        let step = if added == 0 {
+            Duration::nanoseconds(1)
+        } else {
+            Duration::zero()
+        };
+        match self.direction {
+            // The end of a range is exclusive, so it has to be one nanosecond later to include the boundary
+            Direction::Backward => Next::Page(boundary + Duration::nanoseconds(1) - step),
+            Direction::Forward => Next::Page(boundary + step),
+        }
+    }
+
+    pub fn finish(self) -> PagedResult {
+        let direction = self.direction;
+        let streams = self
+            .streams
+            .into_iter()
+            .map(|(labels, mut values)| {
+                values.sort_by_key(|value| value.timestamp);
+                if direction == Direction::Backward {
+                    values.reverse();
+                }
+                LokiStream {
+                    labels: labels.into_iter().collect(),
+                    values,
+                }
+            })
+            .collect();
+        PagedResult {
+            result: LokiResult::Streams(streams),
+            lines: self.lines,
+            truncated: self.truncated,
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use std::{
+    collections::BTreeMap,
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Local};
+use loki_api::prost_types::Timestamp;
+
+use super::{
+    error::Error,
+    labels::Labels,
+    proto::{EntryAdapter, LabelPairAdapter, StreamAdapter},
+    Loki,
+};
+
+/// Log lines sharing the same labels, as sent to Loki
+#[derive(Clone, Debug, PartialEq)]
+pub struct PushStream {
+    pub labels: Labels,
+    pub entries: Vec<PushEntry>,
+}
+
+/// A single log line to push
+#[derive(Clone, Debug, PartialEq)]
+pub struct PushEntry {
+    pub timestamp: DateTime<Local>,
+    pub line: String,
+    /// Key value pairs stored with the line but not indexed, like trace ids. Needs Loki 3.
+    pub structured_metadata: BTreeMap<String, String>,
+}
+
+impl PushEntry {
+    pub fn new(timestamp: DateTime<Local>, line: String) -> Self {
+        Self {
+            timestamp,
+            line,
+            structured_metadata: BTreeMap::new(),
+        }
+    }
+
+    /// An entry with the current time
+    pub fn now(line: String) -> Self {
+        Self::new(Local::now(), line)
+    }
+
+    /// Adds a structured metadata pair to the entry
+    #[must_use]
+    pub fn with_metadata(mut self, name: &str, value: &str) -> Self {
+        self.structured_metadata
+            .insert(name.to_string(), value.to_string());
+        self
+    }
+}
+
+impl From<&PushStream> for StreamAdapter {
+    fn from(stream: &PushStream) -> Self {
+        StreamAdapter {
+            labels: stream.labels.to_string(),
+            entries: stream
+                .entries
+                .iter()
+                .map(|entry| EntryAdapter {
+                    timestamp: Some(Timestamp {
+                        seconds: entry.timestamp.timestamp(),
+                        nanos: entry.timestamp.timestamp_subsec_nanos() as i32,
+                    }),
+                    line: entry.line.clone(),
+                    structured_metadata: entry
+                        .structured_metadata
+                        .iter()
+                        .map(|(name, value)| LabelPairAdapter {
+                            name: name.clone(),
+                            value: value.clone(),
+                        })
+                        .collect(),
+                })
+                .collect(),
+            hash: 0,
+        }
+    }
+}
+
+///
+/// Collects log lines and pushes them together, like promtail does.
+/// A batch is sent once it holds `max_bytes` of log lines, or when a line is added
+/// after the oldest one waited `max_wait`. Call [`Batch::flush`] to send the rest.
+///
+/// # Example
+/// ```no_run
+/// use loki_ui::loki::{labels::Labels, push::{Batch, PushEntry}, Loki};
+///
+/// let mut batch = Batch::new(Loki::new(String::from("http://localhost:3100")));
+/// let labels = Labels::new().with("job", "fixture").unwrap();
+/// for i in 0..10_000 {
+///     batch.add(&labels, PushEntry::now(format!("line {i}"))).unwrap();
+/// }
+/// batch.flush().unwrap();
+/// ```
+pub struct Batch {
+    pub loki: Loki,
+    /// Sends the batch once its lines and labels take up this many bytes, 1 MiB by default
+    pub max_bytes: usize,
+    /// Sends the batch once its oldest line waited this long, 1 second by default
+    pub max_wait: Duration,
+    streams: BTreeMap<Labels, Vec<PushEntry>>,
+    bytes: usize,
+    /// When the first line of the current batch was added
+    started: Option<Instant>,
+}
+
+impl Batch {
+    pub fn new(loki: Loki) -> Self {
+        Self {
+            loki,
+            max_bytes: 1024 * 1024,
+            max_wait: Duration::from_secs(1),
+            streams: BTreeMap::new(),
+            bytes: 0,
+            started: None,
+        }
+    }
+
+    /// Adds a line to the batch, and sends the batch if it is full or waited long enough
+    pub fn add(&mut self, labels: &Labels, entry: PushEntry) -> Result<(), Error> {
+        let entries = match self.streams.get_mut(labels) {
+            Some(entries) => entries,
+            None => {
+                self.bytes += labels.to_string().len();
+                self.streams.entry(labels.clone()).or_default()
+            }
+        };
+        self.bytes += entry.line.len()
+            + entry
+                .structured_metadata
+                .iter()
+                .map(|(name, value)| name.len() + value.len())
+                .sum::<usize>();
+        entries.push(entry);
+        let started = *self.started.get_or_insert_with(Instant::now);
+
+        if self.bytes >= self.max_bytes || started.elapsed() >= self.max_wait {
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    /// The number of lines waiting to be sent
+    pub fn len(&self) -> usize {
+        self.streams.values().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.streams.is_empty()
+    }
+
+    /// Sends all waiting lines. If that fails they are kept, so flushing can be retried.
+    pub fn flush(&mut self) -> Result<(), Error> {
+        if self.streams.is_empty() {
+            return Ok(());
+        }
+        let streams: Vec<PushStream> = self
+            .streams
+            .iter()
+            .map(|(labels, entries)| PushStream {
+                labels: labels.clone(),
+                entries: entries.clone(),
+            })
+            .collect();
+        self.loki.push(&streams)?;
+
+        self.streams.clear();
+        self.bytes = 0;
+        self.started = None;
+        Ok(())
+    }
+}
@@ -0,0 +1,242 @@
+use std::{collections::HashMap, fmt::Display};
+
+use chrono::{DateTime, Local, TimeZone};
+use serde_json::Value;
+
+use super::error::{api_error, Error};
+
+/// The result of a query, depending on the `resultType` Loki answered with
+#[derive(Debug)]
+pub enum LokiResult {
+    /// Log lines, returned by log queries
+    Streams(Vec<LokiStream>),
+    /// Samples over time, returned by metric range queries
+    Matrix(Vec<LokiSeries>),
+    /// A single sample per series, returned by metric instant queries
+    Vector(Vec<LokiInstantSample>),
+    /// A single number, returned by instant queries like `vector(1) * 2`
+    Scalar(LokiSample),
+}
+
+impl LokiResult {
+    /// Parses the body of a `query` or `query_range` response
+    pub fn from_json(text: &Value) -> Result<Self, Error> {
+        if let Some(error) = api_error(text) {
+            return Err(error);
+        }
+        let result_type = text
+            .pointer("/data/resultType")
+            .and_then(Value::as_str)
+            .ok_or_else(|| Error::decode(String::from("Response contains no resultType")))?;
+        let result = text
+            .pointer("/data/result")
+            .ok_or_else(|| Error::decode(String::from("Response contains no result")))?;
+
+        match result_type {
+            "streams" => Ok(Self::Streams(
+                array(result, "result")?
+                    .iter()
+                    .map(LokiStream::from_json)
+                    .collect::<Result<_, _>>()?,
+            )),
+            "matrix" => Ok(Self::Matrix(
+                array(result, "result")?
+                    .iter()
+                    .map(LokiSeries::from_json)
+                    .collect::<Result<_, _>>()?,
+            )),
+            "vector" => Ok(Self::Vector(
+                array(result, "result")?
+                    .iter()
+                    .map(LokiInstantSample::from_json)
+                    .collect::<Result<_, _>>()?,
+            )),
+            "scalar" => Ok(Self::Scalar(LokiSample::from_json(result)?)),
+            _ => Err(Error::decode(format!("Unknown result type: {result_type}"))),
+        }
+    }
+
+    /// Returns true if the query matched nothing
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Self::Streams(streams) => streams.is_empty(),
+            Self::Matrix(series) => series.is_empty(),
+            Self::Vector(samples) => samples.is_empty(),
+            Self::Scalar(_) => false,
+        }
+    }
+}
+
+/// A stream of log lines sharing the same labels
+#[derive(Debug)]
+pub struct LokiStream {
+    pub labels: HashMap<String, String>,
+    pub values: Vec<LokiValue>,
+}
+
+impl LokiStream {
+    pub(crate) fn from_json(stream: &Value) -> Result<Self, Error> {
+        let labels = labels(&stream["stream"], "stream")?;
+        let values = array(&stream["values"], "values")?
+            .iter()
+            .map(|value| {
+                let timestamp = string(&value[0], "timestamp")?;
+                let log_line = string(&value[1], "log line")?;
+                let mut entry = LokiValue::from_nano(timestamp.to_owned(), log_line.to_owned())?;
+                entry.read_categorized_labels(&value[2])?;
+                Ok(entry)
+            })
+            .collect::<Result<_, Error>>()?;
+        Ok(Self { labels, values })
+    }
+}
+
+/// A metric series with a sample for every step of a range query
+#[derive(Debug)]
+pub struct LokiSeries {
+    pub metric: HashMap<String, String>,
+    pub values: Vec<LokiSample>,
+}
+
+impl LokiSeries {
+    fn from_json(series: &Value) -> Result<Self, Error> {
+        let metric = labels(&series["metric"], "metric")?;
+        let values = array(&series["values"], "values")?
+            .iter()
+            .map(LokiSample::from_json)
+            .collect::<Result<_, _>>()?;
+        Ok(Self { metric, values })
+    }
+}
+
+/// A metric series with a single sample, as returned by instant queries
+#[derive(Debug)]
+pub struct LokiInstantSample {
+    pub metric: HashMap<String, String>,
+    pub value: LokiSample,
+}
+
+impl LokiInstantSample {
+    fn from_json(sample: &Value) -> Result<Self, Error> {
+        Ok(Self {
+            metric: labels(&sample["metric"], "metric")?,
+            value: LokiSample::from_json(&sample["value"])?,
+        })
+    }
+}
+
+/// A single metric sample
+#[derive(Debug)]
+pub struct LokiSample {
+    pub timestamp: DateTime<Local>,
+    pub value: f64,
+}
+
+impl LokiSample {
+    /// Samples are sent as `[<unix seconds as float>, "<value>"]`
+    fn from_json(sample: &Value) -> Result<Self, Error> {
+        let timestamp = sample[0]
+            .as_f64()
+            .ok_or_else(|| Error::decode(format!("Invalid sample timestamp: {}", sample[0])))?;
+        let value = string(&sample[1], "sample value")?;
+        let value = value
+            .parse::<f64>()
+            .map_err(|e| Error::decode_with(e, format!("Invalid sample value: {value}")))?;
+
+        // Rounding the whole value carries e.g. `x.9999999999` over into the next second
+        let nanos = (timestamp * 1_000_000_000.0).round();
+        if !(i64::MIN as f64..i64::MAX as f64).contains(&nanos) {
+            return Err(Error::decode(format!(
+                "Sample timestamp out of range: {timestamp}"
+            )));
+        }
+        let timestamp = Local.timestamp_nanos(nanos as i64);
+        Ok(Self { timestamp, value })
+    }
+}
+
+impl Display for LokiSample {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}] {}", self.timestamp, self.value)
+    }
+}
+
+/// A single log line
+#[derive(Debug)]
+pub struct LokiValue {
+    pub timestamp: DateTime<Local>,
+    pub log_line: String,
+    /// The structured metadata pushed with the line, like trace ids
+    pub structured_metadata: HashMap<String, String>,
+    /// Labels extracted by parsers like `| json`
+    pub parsed: HashMap<String, String>,
+}
+
+impl LokiValue {
+    /// Creates a value from a unix timestamp in nanoseconds, as Loki sends them
+    pub fn from_nano(timestamp: String, log_line: String) -> Result<Self, Error> {
+        Ok(Self {
+            timestamp: parse_nano(&timestamp)?,
+            log_line,
+            structured_metadata: HashMap::new(),
+            parsed: HashMap::new(),
+        })
+    }
+
+    ///
+    /// Reads the optional third element of a value.
+    /// Loki 3 sends `{"structuredMetadata": {..}, "parsed": {..}}` when asked to categorize labels,
+    /// older versions send the structured metadata as a plain object.
+    fn read_categorized_labels(&mut self, categories: &Value) -> Result<(), Error> {
+        if categories.is_null() {
+            return Ok(());
+        }
+        if categories.get("structuredMetadata").is_none() && categories.get("parsed").is_none() {
+            self.structured_metadata = labels(categories, "structured metadata")?;
+            return Ok(());
+        }
+        if let Some(metadata) = categories.get("structuredMetadata") {
+            self.structured_metadata = labels(metadata, "structured metadata")?;
+        }
+        if let Some(parsed) = categories.get("parsed") {
+            self.parsed = labels(parsed, "parsed labels")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for LokiValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}] {}", self.timestamp, self.log_line)
+    }
+}
+
+/// Parses a unix timestamp in nanoseconds, as sent for log lines
+pub(crate) fn parse_nano(timestamp: &str) -> Result<DateTime<Local>, Error> {
+    let timestamp = timestamp
+        .parse::<i64>()
+        .map_err(|e| Error::decode_with(e, format!("Invalid timestamp: {timestamp}")))?;
+    // Converting through the time zone, and not the current offset, keeps daylight saving time right
+    Ok(Local.timestamp_nanos(timestamp))
+}
+
+fn array<'a>(value: &'a Value, name: &str) -> Result<&'a Vec<Value>, Error> {
+    value
+        .as_array()
+        .ok_or_else(|| Error::decode(format!("Expected {name} to be an array, got: {value}")))
+}
+
+pub(crate) fn string<'a>(value: &'a Value, name: &str) -> Result<&'a str, Error> {
+    value
+        .as_str()
+        .ok_or_else(|| Error::decode(format!("Expected {name} to be a string, got: {value}")))
+}
+
+pub(crate) fn labels(value: &Value, name: &str) -> Result<HashMap<String, String>, Error> {
+    value
+        .as_object()
+        .ok_or_else(|| Error::decode(format!("Expected {name} to be an object, got: {value}")))?
+        .iter()
+        .map(|(key, value)| Ok((key.to_owned(), string(value, "label value")?.to_owned())))
+        .collect()
+}
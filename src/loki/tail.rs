@@ -0,0 +1,117 @@
+use std::{collections::HashMap, net::TcpStream, time::Duration};
+
+use chrono::{DateTime, Local};
+use serde_json::Value;
+use tungstenite::{stream::MaybeTlsStream, Message, WebSocket};
+
+use super::{
+    error::Error,
+    result::{labels, parse_nano, string, LokiStream},
+};
+
+///
+/// A websocket connection to `/loki/api/v1/tail`.
+/// Loki pushes new log lines matching the query as they are ingested.
+pub struct Tail {
+    socket: WebSocket<MaybeTlsStream<TcpStream>>,
+}
+
+/// A batch of log lines sent by Loki while tailing
+#[derive(Debug)]
+pub struct TailResponse {
+    pub streams: Vec<LokiStream>,
+    /// Lines Loki skipped because the client was not reading fast enough
+    pub dropped_entries: Vec<DroppedEntry>,
+}
+
+#[derive(Debug)]
+pub struct DroppedEntry {
+    pub labels: HashMap<String, String>,
+    pub timestamp: DateTime<Local>,
+}
+
+impl Tail {
+    pub(crate) fn new(socket: WebSocket<MaybeTlsStream<TcpStream>>) -> Self {
+        Self { socket }
+    }
+
+    /// Limits how long [`Tail::read`] blocks while waiting for new lines
+    pub fn set_read_timeout(&mut self, timeout: Option<Duration>) -> Result<(), Error> {
+        let result = match self.socket.get_mut() {
+            MaybeTlsStream::Plain(stream) => stream.set_read_timeout(timeout),
+            MaybeTlsStream::NativeTls(stream) => stream.get_ref().set_read_timeout(timeout),
+            _ => Ok(()),
+        };
+        result.map_err(|e| Error::Transport(Box::new(e)))
+    }
+
+    /// Waits for the next batch of lines.
+    /// Returns `None` if the read timeout expired before Loki sent anything.
+    pub fn read(&mut self) -> Result<Option<TailResponse>, Error> {
+        let message = match self.socket.read() {
+            Ok(message) => message,
+            Err(tungstenite::Error::Io(e))
+                if matches!(
+                    e.kind(),
+                    std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                ) =>
+            {
+                return Ok(None);
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        match message {
+            Message::Text(text) => {
+                let text: Value = serde_json::from_str(&text).map_err(|e| {
+                    Error::decode_with(e, String::from("Error parsing tail response"))
+                })?;
+                TailResponse::from_json(&text).map(Some)
+            }
+            Message::Close(frame) => Err(Error::Transport(Box::new(std::io::Error::new(
+                std::io::ErrorKind::ConnectionAborted,
+                format!(
+                    "Loki closed the tail connection: {}",
+                    frame
+                        .map(|frame| frame.reason.into_owned())
+                        .unwrap_or_default()
+                ),
+            )))),
+            _ => Ok(None),
+        }
+    }
+
+    /// Closes the connection, ignoring errors since we are done with it anyway
+    pub fn close(mut self) {
+        let _ = self.socket.close(None);
+        let _ = self.socket.flush();
+    }
+}
+
+impl TailResponse {
+    fn from_json(text: &Value) -> Result<Self, Error> {
+        let streams = match &text["streams"] {
+            Value::Array(streams) => streams
+                .iter()
+                .map(LokiStream::from_json)
+                .collect::<Result<_, _>>()?,
+            _ => Vec::new(),
+        };
+        let dropped_entries = match &text["dropped_entries"] {
+            Value::Array(entries) => entries
+                .iter()
+                .map(|entry| {
+                    Ok(DroppedEntry {
+                        labels: labels(&entry["labels"], "labels")?,
+                        timestamp: parse_nano(string(&entry["timestamp"], "timestamp")?)?,
+                    })
+                })
+                .collect::<Result<_, Error>>()?,
+            _ => Vec::new(),
+        };
+        Ok(Self {
+            streams,
+            dropped_entries,
+        })
+    }
+}
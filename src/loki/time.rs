@@ -0,0 +1,191 @@
+use std::fmt::Display;
+
+use chrono::{
+    DateTime, Datelike, Duration, Local, LocalResult, Months, NaiveDate, NaiveDateTime,
+    SecondsFormat, TimeZone, Timelike,
+};
+
+use super::{error::Error, logql::parse_duration};
+
+///
+/// A time range as the user entered it, e.g. `now-15m` to `now`.
+/// Relative expressions are resolved every time the range is used.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeRange {
+    pub from: String,
+    pub to: String,
+}
+
+impl TimeRange {
+    pub fn new(from: &str, to: &str) -> Self {
+        Self {
+            from: from.trim().to_string(),
+            to: to.trim().to_string(),
+        }
+    }
+
+    /// The range from `duration` ago until now
+    pub fn last(duration: &str) -> Self {
+        Self::new(&format!("now-{duration}"), "now")
+    }
+
+    /// The absolute start and end of the range at the time of calling
+    pub fn resolve(&self) -> Result<(DateTime<Local>, DateTime<Local>), Error> {
+        let now = Local::now();
+        let from = parse_time(&self.from, now, false)?;
+        let to = parse_time(&self.to, now, true)?;
+        if from >= to {
+            return Err(Error::InvalidInput(format!(
+                "The start {} is not before the end {}",
+                from.to_rfc3339(),
+                to.to_rfc3339()
+            )));
+        }
+        Ok((from, to))
+    }
+}
+
+impl Default for TimeRange {
+    /// The last 6 hours, the range used when none is given
+    fn default() -> Self {
+        Self::last("6h")
+    }
+}
+
+impl Display for TimeRange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} to {}", self.from, self.to)
+    }
+}
+
+///
+/// Parses a time like Grafana does. Supported are:
+/// - relative times like `now`, `now-15m`, `now+1h` and `now-2d/d`,
+///   where `/d` rounds to the start of the day, or its end if `round_up` is set
+/// - RFC3339 times like `2024-01-23T20:58:22+01:00`
+/// - local times like `2024-01-23 20:58:22`, `2024-01-23 20:58` and `2024-01-23`
+/// - unix timestamps in seconds, milliseconds, microseconds or nanoseconds
+pub fn parse_time(
+    expr: &str,
+    now: DateTime<Local>,
+    round_up: bool,
+) -> Result<DateTime<Local>, Error> {
+    let expr = expr.trim();
+    let invalid = || Error::InvalidInput(format!("Invalid time: {expr:?}"));
+
+    if let Some(relative) = expr.strip_prefix("now") {
+        let (offset, rounding) = match relative.split_once('/') {
+            Some((offset, rounding)) => (offset, Some(rounding)),
+            None => (relative, None),
+        };
+
+        let mut time = now;
+        if !offset.is_empty() {
+            let (sign, duration) = offset.split_at(1);
+            let duration = parse_duration(duration)
+                .and_then(|duration| Duration::from_std(duration).ok())
+                .ok_or_else(invalid)?;
+            time = match sign {
+                "-" => time.checked_sub_signed(duration),
+                "+" => time.checked_add_signed(duration),
+                _ => return Err(invalid()),
+            }
+            .ok_or_else(|| Error::InvalidInput(format!("The time {expr:?} is out of range")))?;
+        }
+        if let Some(unit) = rounding {
+            time = round(time, unit, round_up).ok_or_else(invalid)?;
+        }
+        return Ok(time);
+    }
+
+    if let Ok(time) = DateTime::parse_from_rfc3339(expr) {
+        return Ok(time.with_timezone(&Local));
+    }
+    for format in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M:%S"] {
+        if let Ok(time) = NaiveDateTime::parse_from_str(expr, format) {
+            return local(time).ok_or_else(invalid);
+        }
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(expr, "%Y-%m-%d") {
+        return local(date.and_hms_opt(0, 0, 0).unwrap()).ok_or_else(invalid);
+    }
+
+    if !expr.is_empty() && expr.chars().all(|c| c.is_ascii_digit()) {
+        let value: i64 = expr.parse().map_err(|_| invalid())?;
+        // Guess the unit from the number of digits, like 1700000000 for seconds
+        let nanos = match expr.len() {
+            0..=11 => value.checked_mul(1_000_000_000),
+            12..=14 => value.checked_mul(1_000_000),
+            15..=17 => value.checked_mul(1_000),
+            _ => Some(value),
+        };
+        return nanos
+            .map(|nanos| Local.timestamp_nanos(nanos))
+            .ok_or_else(invalid);
+    }
+
+    Err(invalid())
+}
+
+///
+/// Formats a time as unix nanoseconds, the most precise format Loki accepts.
+/// Times that don't fit into nanoseconds (before 1677 or after 2262) are sent as RFC3339.
+pub fn to_nanos(time: &DateTime<Local>) -> String {
+    match time.timestamp_nanos_opt() {
+        Some(nanos) => nanos.to_string(),
+        None => time.to_rfc3339_opts(SecondsFormat::Nanos, true),
+    }
+}
+
+/// Resolves a local time, picking the earlier time when it is ambiguous
+fn local(time: NaiveDateTime) -> Option<DateTime<Local>> {
+    match Local.from_local_datetime(&time) {
+        LocalResult::Single(time) | LocalResult::Ambiguous(time, _) => Some(time),
+        LocalResult::None => None,
+    }
+}
+
+/// Rounds down to the start of the unit, or up to the last nanosecond of it
+fn round(time: DateTime<Local>, unit: &str, round_up: bool) -> Option<DateTime<Local>> {
+    let naive = time.naive_local();
+    let date = naive.date();
+    let (start, next) = match unit {
+        "s" => {
+            let start = naive.with_nanosecond(0)?;
+            (start, start.checked_add_signed(Duration::seconds(1))?)
+        }
+        "m" => {
+            let start = naive.with_second(0)?.with_nanosecond(0)?;
+            (start, start.checked_add_signed(Duration::minutes(1))?)
+        }
+        "h" => {
+            let start = date.and_hms_opt(naive.hour(), 0, 0)?;
+            (start, start.checked_add_signed(Duration::hours(1))?)
+        }
+        "d" => {
+            let start = date.and_hms_opt(0, 0, 0)?;
+            (start, start.checked_add_signed(Duration::days(1))?)
+        }
+        "w" => {
+            let monday = date
+                .checked_sub_signed(Duration::days(date.weekday().num_days_from_monday().into()))?;
+            let start = monday.and_hms_opt(0, 0, 0)?;
+            (start, start.checked_add_signed(Duration::weeks(1))?)
+        }
+        "M" => {
+            let start = date.with_day(1)?.and_hms_opt(0, 0, 0)?;
+            (start, start.checked_add_months(Months::new(1))?)
+        }
+        "y" => {
+            let start = NaiveDate::from_ymd_opt(date.year(), 1, 1)?.and_hms_opt(0, 0, 0)?;
+            (start, start.checked_add_months(Months::new(12))?)
+        }
+        _ => return None,
+    };
+
+    if round_up {
+        local(next.checked_sub_signed(Duration::nanoseconds(1))?)
+    } else {
+        local(start)
+    }
+}
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
 /// The response from GET /loki/api/v1/labels
@@ -6,3 +8,10 @@ pub struct LokiLabels {
     pub status: String,
     pub data: Vec<String>,
 }
+
+/// The response from GET /loki/api/v1/series
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LokiSeriesList {
+    pub status: String,
+    pub data: Vec<HashMap<String, String>>,
+}
@@ -4,7 +4,7 @@ use crossterm::{
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
     ExecutableCommand,
 };
-use loki_ui::{ui::App, LokiConfig};
+use loki_ui::{ui::App, EnvConfig, LokiConfig};
 use ratatui::{backend::CrosstermBackend, Terminal};
 
 fn main() -> io::Result<()> {
@@ -15,14 +15,17 @@ fn main() -> io::Result<()> {
         tui_logger::set_default_level(log::LevelFilter::Trace);
     }
 
+    // The config file, with the environment on top for this run only
+    let stored: LokiConfig = confy::load("loki_ui", None).unwrap();
+    let env = EnvConfig::from_env().map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    let mut cfg = stored.clone();
+    env.apply(&mut cfg);
+    let read_only = std::env::var("LOKI_READ_ONLY").ok();
+    cfg.read_only |= LokiConfig::read_only_override(std::env::args().skip(1), read_only.as_deref());
+
     enable_raw_mode()?;
     stdout().execute(EnterAlternateScreen)?;
     let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
-    let envs = envy::from_env::<LokiConfig>();
-    let cfg = match envs {
-        Ok(envs) => envs,
-        Err(_) => confy::load("loki_ui", None).unwrap(),
-    };
     let mut app = App::new(cfg);
 
     let mut should_quit = false;
@@ -34,6 +37,9 @@ fn main() -> io::Result<()> {
     disable_raw_mode()?;
     stdout().execute(LeaveAlternateScreen)?;
 
+    // Only what was edited during the run is stored, read-only can't be edited
+    env.unapply(&mut app.config, &stored);
+    app.config.read_only = stored.read_only;
     confy::store("loki_ui", None, &app.config).unwrap();
     Ok(())
 }
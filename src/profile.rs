@@ -0,0 +1,129 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+///
+/// A named Loki to switch to, e.g. `dev`, `staging` or `prod`.
+/// Switching copies the connection into the top level settings of the config,
+/// the defaults are only copied if they are set.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    pub name: String,
+    pub loki_url: String,
+    #[serde(default)]
+    pub loki_username: Option<String>,
+    #[serde(default)]
+    pub loki_password: Option<String>,
+    #[serde(default)]
+    pub loki_bearer_token: Option<String>,
+    #[serde(default)]
+    pub loki_tenant_id: Option<String>,
+    /// Refuses deletes and pushes while the profile is active, e.g. for production
+    #[serde(default)]
+    pub read_only: bool,
+    #[serde(default)]
+    pub tail_delay_for: Option<u32>,
+    #[serde(default)]
+    pub saved_queries_path: Option<String>,
+    #[serde(default)]
+    pub max_lines: Option<usize>,
+}
+
+/// A Grafana datasource provisioning file, only the parts needed for Loki
+#[derive(Deserialize)]
+struct Provisioning {
+    #[serde(default)]
+    datasources: Vec<Datasource>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Datasource {
+    name: String,
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    url: Option<String>,
+    #[serde(default)]
+    basic_auth: bool,
+    #[serde(default)]
+    basic_auth_user: Option<String>,
+    #[serde(default)]
+    json_data: HashMap<String, serde_yaml::Value>,
+    #[serde(default)]
+    secure_json_data: HashMap<String, String>,
+}
+
+impl Datasource {
+    /// The value of a custom header, configured as `httpHeaderNameN` and `httpHeaderValueN`
+    fn header(&self, name: &str) -> Option<&str> {
+        self.json_data.iter().find_map(|(key, value)| {
+            let number = key.strip_prefix("httpHeaderName")?;
+            if !value.as_str()?.eq_ignore_ascii_case(name) {
+                return None;
+            }
+            self.secure_json_data
+                .get(&format!("httpHeaderValue{number}"))
+                .map(String::as_str)
+        })
+    }
+
+    fn profile(&self) -> Result<Profile, String> {
+        let url = self
+            .url
+            .as_deref()
+            .filter(|url| !url.is_empty())
+            .ok_or_else(|| format!("The datasource {:?} has no url", self.name))?;
+        let (loki_username, loki_password) = match self.basic_auth {
+            true => (
+                self.basic_auth_user.clone(),
+                self.secure_json_data.get("basicAuthPassword").cloned(),
+            ),
+            false => (None, None),
+        };
+        Ok(Profile {
+            name: self.name.clone(),
+            loki_url: url.trim_end_matches('/').to_string(),
+            loki_username,
+            loki_password,
+            loki_bearer_token: self
+                .header("Authorization")
+                .and_then(|value| value.strip_prefix("Bearer "))
+                .map(str::to_string),
+            loki_tenant_id: self.header("X-Scope-OrgID").map(str::to_string),
+            ..Profile::default()
+        })
+    }
+}
+
+///
+/// Reads the Loki datasources of a Grafana provisioning file as profiles, like:
+/// ```yaml
+/// datasources:
+///   - name: Loki
+///     type: loki
+///     url: http://loki:3100
+/// ```
+/// Basic auth, a bearer token in the `Authorization` header and the tenant in
+/// the `X-Scope-OrgID` header are taken over. Other datasource types are skipped.
+pub fn from_grafana_datasources(content: &str) -> Result<Vec<Profile>, String> {
+    let provisioning: Provisioning =
+        serde_yaml::from_str(content).map_err(|e| format!("Invalid datasource file: {e}"))?;
+    let profiles = provisioning
+        .datasources
+        .iter()
+        .filter(|datasource| datasource.kind == "loki")
+        .map(Datasource::profile)
+        .collect::<Result<Vec<_>, _>>()?;
+    if profiles.is_empty() {
+        return Err(String::from("The file contains no Loki datasources"));
+    }
+    Ok(profiles)
+}
+
+/// Reads the Loki datasources of the Grafana provisioning file at `path`
+pub fn load_grafana_datasources(path: &Path) -> Result<Vec<Profile>, String> {
+    let content =
+        fs::read_to_string(path).map_err(|e| format!("Unable to read {}: {e}", path.display()))?;
+    from_grafana_datasources(&content)
+}
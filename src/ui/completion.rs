@@ -0,0 +1,264 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use chrono::{DateTime, Local};
+use log::error;
+
+use crate::loki::{
+    error::Error,
+    labels::escape,
+    logql::{RANGE_AGGREGATIONS, VECTOR_AGGREGATIONS},
+    Loki,
+};
+
+/// The start and end of the range labels are loaded for
+type Range = (Option<DateTime<Local>>, Option<DateTime<Local>>);
+
+/// Stages that can follow a `|` in a log pipeline
+pub const PIPELINE_KEYWORDS: &[&str] = &[
+    "json",
+    "logfmt",
+    "regexp",
+    "pattern",
+    "unpack",
+    "line_format",
+    "label_format",
+    "drop",
+    "keep",
+    "decolorize",
+    "unwrap",
+];
+
+/// Keywords of metric queries that are not aggregations
+const FUNCTION_MODIFIERS: &[&str] = &["by", "without", "offset", "vector", "label_replace"];
+
+/// Range functions, aggregations and their modifiers
+pub fn function_keywords() -> impl Iterator<Item = &'static str> {
+    RANGE_AGGREGATIONS
+        .iter()
+        .chain(VECTOR_AGGREGATIONS)
+        .chain(FUNCTION_MODIFIERS)
+        .copied()
+}
+
+/// What the word at the cursor is
+#[derive(Debug, PartialEq)]
+pub enum Kind {
+    /// A label name inside a stream selector
+    LabelName,
+    /// The value of the given label. `quoted` is false if the opening quote is missing.
+    LabelValue { label: String, quoted: bool },
+    /// A stage after a `|`
+    Pipeline,
+    /// A function or aggregation
+    Function,
+}
+
+/// The completion context at the cursor
+#[derive(Debug, PartialEq)]
+pub struct Context {
+    pub kind: Kind,
+    /// The char index where the word being completed starts
+    pub start: usize,
+    /// The part of the word left of the cursor
+    pub prefix: String,
+}
+
+impl Context {
+    /// Finds out what is being typed at the char index `cursor` of the query
+    pub fn at(query: &str, cursor: usize) -> Option<Self> {
+        let chars: Vec<char> = query.chars().take(cursor).collect();
+        let cursor = chars.len();
+
+        let mut in_braces = false;
+        // The quote char and the index after it, while inside a string
+        let mut string: Option<(char, usize)> = None;
+        let mut escaped = false;
+        let mut last_ident = String::new();
+        let mut label = None;
+        let mut i = 0;
+        while i < cursor {
+            let char = chars[i];
+            if let Some((quote, _)) = string {
+                if escaped {
+                    escaped = false;
+                } else if char == '\\' && quote == '"' {
+                    escaped = true;
+                } else if char == quote {
+                    string = None;
+                }
+                i += 1;
+                continue;
+            }
+
+            match char {
+                '"' | '`' => string = Some((char, i + 1)),
+                '{' => {
+                    in_braces = true;
+                    label = None;
+                }
+                '}' => in_braces = false,
+                '=' | '!' | '~' if in_braces && !last_ident.is_empty() => {
+                    label = Some(last_ident.clone());
+                }
+                ',' => label = None,
+                _ => {}
+            }
+            if is_ident(char) {
+                if i == 0 || !is_ident(chars[i - 1]) {
+                    last_ident.clear();
+                }
+                last_ident.push(char);
+            }
+            i += 1;
+        }
+
+        if let Some((_, start)) = string {
+            let label = label.filter(|_| in_braces)?;
+            return Some(Self {
+                kind: Kind::LabelValue {
+                    label,
+                    quoted: true,
+                },
+                start,
+                prefix: chars[start..].iter().collect(),
+            });
+        }
+
+        let mut start = cursor;
+        while start > 0 && is_ident(chars[start - 1]) {
+            start -= 1;
+        }
+        let prefix: String = chars[start..].iter().collect();
+        let before = chars[..start]
+            .iter()
+            .rev()
+            .find(|char| !char.is_whitespace());
+
+        let kind = match (in_braces, before) {
+            (true, None | Some('{') | Some(',')) => Kind::LabelName,
+            (true, Some('=') | Some('~')) if prefix.is_empty() => Kind::LabelValue {
+                label: label?,
+                quoted: false,
+            },
+            (true, _) => return None,
+            (false, Some('|')) => Kind::Pipeline,
+            (false, _) if prefix.chars().next().is_some_and(|c| c.is_ascii_digit()) => return None,
+            (false, _) => Kind::Function,
+        };
+        Some(Self {
+            kind,
+            start,
+            prefix,
+        })
+    }
+
+    /// The candidates from `words` that start with the prefix
+    pub fn matching<'a>(&self, words: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+        let mut matching: Vec<String> = words
+            .into_iter()
+            .filter(|word| word.starts_with(&self.prefix) && *word != self.prefix)
+            .map(str::to_string)
+            .collect();
+        matching.sort();
+        matching.dedup();
+        matching
+    }
+
+    /// The text that replaces the prefix when `candidate` is picked
+    pub fn replacement(&self, candidate: &str) -> String {
+        match self.kind {
+            Kind::LabelValue { quoted: true, .. } => escape(candidate),
+            Kind::LabelValue { quoted: false, .. } => format!("\"{}\"", escape(candidate)),
+            _ => candidate.to_string(),
+        }
+    }
+}
+
+fn is_ident(char: char) -> bool {
+    char.is_ascii_alphanumeric() || char == '_'
+}
+
+///
+/// Label names and values for completion, loaded once per session.
+/// Loading happens in the background, until then nothing is offered.
+/// Failed loads are remembered and only tried again after [`LabelCache::retry_failed`],
+/// so an unreachable Loki isn't asked on every redraw.
+#[derive(Default)]
+pub struct LabelCache {
+    labels: Option<Vec<String>>,
+    values: HashMap<String, Vec<String>>,
+    /// The labels whose values are loading, `None` for the label names
+    loading: HashSet<Option<String>>,
+    /// Like `loading`, for what failed to load
+    failed: HashSet<Option<String>>,
+}
+
+impl LabelCache {
+    /// The known label names, starts loading them for `range` if needed
+    pub fn labels(cache: &Arc<Mutex<Self>>, loki: &Loki, range: Range) -> Vec<String> {
+        let mut loki = loki.clone();
+        LabelCache::load(cache, None, move || loki.labels(range.0, range.1))
+    }
+
+    /// The known values of a label, starts loading them for `range` if needed
+    pub fn values(cache: &Arc<Mutex<Self>>, loki: &Loki, label: &str, range: Range) -> Vec<String> {
+        let mut loki = loki.clone();
+        let name = label.to_string();
+        LabelCache::load(cache, Some(label), move || {
+            loki.label_values(&name, range.0, range.1, None)
+        })
+    }
+
+    ///
+    /// The cached label names if `label` is `None`, otherwise the cached values of `label`.
+    /// If they aren't known yet `load` is run in the background, unless it is running or failed.
+    pub fn load(
+        cache: &Arc<Mutex<Self>>,
+        label: Option<&str>,
+        load: impl FnOnce() -> Result<Vec<String>, Error> + Send + 'static,
+    ) -> Vec<String> {
+        let mut locked = cache.lock().unwrap();
+        let cached = match label {
+            None => locked.labels.as_ref(),
+            Some(label) => locked.values.get(label),
+        };
+        if let Some(cached) = cached {
+            return cached.clone();
+        }
+        let key = label.map(str::to_string);
+        if locked.failed.contains(&key) || !locked.loading.insert(key.clone()) {
+            return Vec::new();
+        }
+        let cache = cache.clone();
+        thread::spawn(move || {
+            let result = load();
+            let mut cache = cache.lock().unwrap();
+            cache.loading.remove(&key);
+            match (result, key) {
+                (Ok(labels), None) => cache.labels = Some(labels),
+                (Ok(values), Some(label)) => {
+                    cache.values.insert(label, values);
+                }
+                (Err(error), key) => {
+                    match &key {
+                        None => error!("Unable to load labels for completion: {error}"),
+                        Some(label) => {
+                            error!("Unable to load values of {label} for completion: {error}")
+                        }
+                    }
+                    cache.failed.insert(key);
+                }
+            }
+        });
+        Vec::new()
+    }
+
+    /// Allows loading what failed before again, e.g. when completion is asked for explicitly
+    pub fn retry_failed(&mut self) {
+        self.failed.clear();
+    }
+}
@@ -0,0 +1,149 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use chrono::{DateTime, Local};
+
+use crate::loki::{labels, Loki};
+
+use super::Store;
+
+/// How many lines are kept in the results while following
+const MAX_LINES: usize = 5000;
+
+///
+/// Follows a query by tailing it in a background thread.
+/// New lines are appended to the results in the store.
+pub struct Follow {
+    stop: Arc<AtomicBool>,
+    paused: Arc<AtomicBool>,
+}
+
+impl Follow {
+    pub fn start(loki: Loki, query: String, delay_for: u32, store: Arc<Mutex<Store>>) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let paused = Arc::new(AtomicBool::new(false));
+        let follow = Self {
+            stop: stop.clone(),
+            paused: paused.clone(),
+        };
+
+        thread::spawn(move || {
+            {
+                let mut store = store.lock().unwrap();
+                store.results = Vec::new();
+                store.status = Some(String::from("connecting"));
+                store.results_changed = true;
+            }
+
+            let tail = loki
+                .tail(&query, Some(delay_for), None, None)
+                .and_then(|mut tail| {
+                    tail.set_read_timeout(Some(Duration::from_millis(200)))?;
+                    Ok(tail)
+                });
+            let mut tail = match tail {
+                Ok(tail) => tail,
+                Err(e) => {
+                    let mut store = store.lock().unwrap();
+                    store.results = e.to_string().lines().map(str::to_string).collect();
+                    store.status = None;
+                    store.results_changed = true;
+                    return;
+                }
+            };
+
+            let mut buffered: Vec<(DateTime<Local>, String)> = Vec::new();
+            let mut dropped = 0;
+            let mut last_status = None;
+            while !stop.load(Ordering::Relaxed) {
+                let mut error = None;
+                match tail.read() {
+                    Ok(Some(response)) => {
+                        dropped += response.dropped_entries.len();
+                        for stream in response.streams {
+                            let labels = format_labels(&stream.labels);
+                            for value in stream.values {
+                                buffered.push((
+                                    value.timestamp,
+                                    format!("[{}] {} {}", value.timestamp, labels, value.log_line),
+                                ));
+                            }
+                        }
+                    }
+                    Ok(None) => {}
+                    Err(e) => error = Some(e),
+                }
+
+                let paused = paused.load(Ordering::Relaxed);
+                let status = match (paused, buffered.len()) {
+                    (true, 0) => format!("paused, {dropped} dropped"),
+                    (true, n) => format!("paused, {n} new, {dropped} dropped"),
+                    (false, _) => format!("following, {dropped} dropped"),
+                };
+
+                let mut store = store.lock().unwrap();
+                if !paused && !buffered.is_empty() {
+                    buffered.sort_by_key(|(timestamp, _)| *timestamp);
+                    store
+                        .results
+                        .extend(buffered.drain(..).map(|(_, line)| line));
+                    let overflow = store.results.len().saturating_sub(MAX_LINES);
+                    store.results.drain(..overflow);
+                    store.results_changed = true;
+                }
+                if let Some(e) = error {
+                    store
+                        .results
+                        .extend(e.to_string().lines().map(str::to_string));
+                    store.status = None;
+                    store.results_changed = true;
+                    return;
+                }
+                if last_status.as_ref() != Some(&status) {
+                    store.status = Some(status.clone());
+                    store.results_changed = true;
+                    last_status = Some(status);
+                }
+            }
+            tail.close();
+
+            let mut store = store.lock().unwrap();
+            store.status = None;
+            store.results_changed = true;
+        });
+
+        follow
+    }
+
+    /// Pauses or resumes adding new lines to the results.
+    /// Lines received while paused are shown once resumed.
+    pub fn toggle_pause(&self) {
+        self.paused.fetch_xor(true, Ordering::Relaxed);
+    }
+
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Drop for Follow {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Formats labels the way Loki prints them: `{job="a", instance="b"}`
+pub fn format_labels(labels: &HashMap<String, String>) -> String {
+    labels::format(
+        labels
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str())),
+    )
+}
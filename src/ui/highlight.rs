@@ -0,0 +1,211 @@
+use std::ops::Range;
+
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::Paragraph,
+};
+use tui_textarea::TextArea;
+
+use crate::loki::logql::{
+    lexer::{Lexer, TokenKind},
+    RANGE_AGGREGATIONS, VECTOR_AGGREGATIONS,
+};
+
+use super::completion::PIPELINE_KEYWORDS;
+
+/// Modifiers and operators that are written as words
+const KEYWORDS: &[&str] = &[
+    "by",
+    "without",
+    "on",
+    "ignoring",
+    "group_left",
+    "group_right",
+    "bool",
+    "offset",
+    "or",
+    "and",
+    "unless",
+    "ip",
+];
+
+///
+/// The style of every part of a query, as byte ranges covering all of it.
+/// Everything after a lexer error is marked red.
+pub fn styles(query: &str) -> Vec<(Range<usize>, Style)> {
+    let mut styles = Vec::new();
+    let mut end = 0;
+    let mut brace_depth = 0;
+    let mut lexer = Lexer::new(query).peekable();
+
+    while let Some(token) = lexer.next() {
+        let token = match token {
+            Ok(token) => token,
+            Err(error) => {
+                let start = error.span.start.min(query.len()).max(end);
+                push_gap(&mut styles, query, end, start);
+                styles.push((
+                    start..query.len(),
+                    Style::default()
+                        .fg(Color::Red)
+                        .add_modifier(Modifier::UNDERLINED),
+                ));
+                return styles;
+            }
+        };
+        push_gap(&mut styles, query, end, token.span.start);
+        end = token.span.end;
+
+        let next_is_operator = matches!(
+            lexer.peek(),
+            Some(Ok(next)) if matches!(
+                next.kind,
+                TokenKind::Eq
+                    | TokenKind::EqEq
+                    | TokenKind::Neq
+                    | TokenKind::Re
+                    | TokenKind::Nre
+                    | TokenKind::Gt
+                    | TokenKind::Ge
+                    | TokenKind::Lt
+                    | TokenKind::Le
+            )
+        );
+        let style = match &token.kind {
+            TokenKind::Ident(ident) => {
+                let ident = ident.as_str();
+                if RANGE_AGGREGATIONS.contains(&ident)
+                    || VECTOR_AGGREGATIONS.contains(&ident)
+                    || matches!(ident, "vector" | "label_replace")
+                {
+                    Style::default().fg(Color::Magenta)
+                } else if next_is_operator {
+                    Style::default().fg(Color::Cyan)
+                } else if PIPELINE_KEYWORDS.contains(&ident) || KEYWORDS.contains(&ident) {
+                    Style::default()
+                        .fg(Color::Blue)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(Color::Cyan)
+                }
+            }
+            TokenKind::String(_) => Style::default().fg(Color::Green),
+            TokenKind::Number(_) | TokenKind::Duration(_) | TokenKind::Bytes(_) => {
+                Style::default().fg(Color::LightYellow)
+            }
+            TokenKind::LBrace => {
+                brace_depth += 1;
+                Style::default()
+                    .fg(Color::White)
+                    .add_modifier(Modifier::BOLD)
+            }
+            TokenKind::RBrace => {
+                brace_depth -= 1;
+                Style::default()
+                    .fg(Color::White)
+                    .add_modifier(Modifier::BOLD)
+            }
+            TokenKind::Pipe
+            | TokenKind::PipeExact
+            | TokenKind::PipeMatch
+            | TokenKind::PipePattern
+            | TokenKind::NotPattern => Style::default().fg(Color::LightRed),
+            // Outside of selectors these start line filters
+            TokenKind::Neq | TokenKind::Nre if brace_depth == 0 => {
+                Style::default().fg(Color::LightRed)
+            }
+            TokenKind::Flag(_) => Style::default().fg(Color::Blue),
+            TokenKind::LParen
+            | TokenKind::RParen
+            | TokenKind::LBracket
+            | TokenKind::RBracket
+            | TokenKind::Comma => Style::default(),
+            _ => Style::default().fg(Color::Yellow),
+        };
+        styles.push((token.span, style));
+    }
+    push_gap(&mut styles, query, end, query.len());
+    styles
+}
+
+/// Whitespace is left alone, comments are dimmed
+fn push_gap(styles: &mut Vec<(Range<usize>, Style)>, query: &str, start: usize, end: usize) {
+    if start >= end {
+        return;
+    }
+    let style = if query[start..end].trim().is_empty() {
+        Style::default()
+    } else {
+        Style::default().fg(Color::DarkGray)
+    };
+    styles.push((start..end, style));
+}
+
+///
+/// The highlighted query as a line.
+/// `cursor` is a char index, the char under it is shown reversed.
+pub fn line(query: &str, cursor: Option<usize>) -> Line<'static> {
+    let cursor_byte = cursor.map(|cursor| {
+        query
+            .char_indices()
+            .nth(cursor)
+            .map_or(query.len(), |(index, _)| index)
+    });
+
+    let mut spans = Vec::new();
+    for (range, style) in styles(query) {
+        match cursor_byte {
+            Some(cursor) if range.contains(&cursor) => {
+                let char_end = cursor + query[cursor..].chars().next().map_or(0, char::len_utf8);
+                if range.start < cursor {
+                    spans.push(Span::styled(query[range.start..cursor].to_string(), style));
+                }
+                spans.push(Span::styled(
+                    query[cursor..char_end].to_string(),
+                    style.add_modifier(Modifier::REVERSED),
+                ));
+                if char_end < range.end {
+                    spans.push(Span::styled(query[char_end..range.end].to_string(), style));
+                }
+            }
+            _ => spans.push(Span::styled(query[range].to_string(), style)),
+        }
+    }
+    if cursor_byte == Some(query.len()) {
+        spans.push(Span::styled(
+            " ",
+            Style::default().add_modifier(Modifier::REVERSED),
+        ));
+    }
+    Line::from(spans)
+}
+
+///
+/// A highlighted, single line view of a query text area.
+/// Scrolls horizontally so the cursor stays within `width`,
+/// returns the paragraph and how many columns it is scrolled by.
+pub fn query_paragraph(
+    textarea: &TextArea,
+    placeholder: &str,
+    width: u16,
+) -> (Paragraph<'static>, u16) {
+    let (_, cursor) = textarea.cursor();
+    let query = textarea.lines()[0].as_str();
+    if query.is_empty() {
+        let line = Line::from(vec![
+            Span::styled(" ", Style::default().add_modifier(Modifier::REVERSED)),
+            Span::styled(
+                placeholder.to_string(),
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]);
+        return (Paragraph::new(line), 0);
+    }
+
+    let scroll = (cursor as u16 + 1).saturating_sub(width);
+    (
+        Paragraph::new(line(query, Some(cursor))).scroll((0, scroll)),
+        scroll,
+    )
+}
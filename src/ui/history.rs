@@ -0,0 +1,113 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::PathBuf,
+};
+
+use chrono::{DateTime, Local};
+use log::error;
+use serde::{Deserialize, Serialize};
+
+/// A query that was sent to Loki
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub timestamp: DateTime<Local>,
+    /// The Loki the query was sent to
+    pub url: String,
+    pub query: String,
+}
+
+///
+/// All submitted queries, oldest first.
+/// Stored as json lines in `history.jsonl` next to the config file.
+#[derive(Default)]
+pub struct History {
+    path: Option<PathBuf>,
+    entries: Vec<HistoryEntry>,
+}
+
+impl History {
+    /// Loads the history from its default location next to the config file
+    pub fn load() -> Self {
+        match confy::get_configuration_file_path("loki_ui", None) {
+            Ok(config) => Self::open(config.with_file_name("history.jsonl")),
+            Err(e) => {
+                error!("Unable to find the history file: {e}");
+                Self::default()
+            }
+        }
+    }
+
+    /// Loads the history from `path`, lines that can't be read are skipped
+    pub fn open(path: PathBuf) -> Self {
+        let entries = fs::read_to_string(&path)
+            .map(|content| {
+                content
+                    .lines()
+                    .filter_map(|line| serde_json::from_str(line).ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self {
+            path: Some(path),
+            entries,
+        }
+    }
+
+    pub fn entries(&self) -> &[HistoryEntry] {
+        &self.entries
+    }
+
+    /// Adds a query and appends it to the history file
+    pub fn push(&mut self, url: &str, query: &str) {
+        let entry = HistoryEntry {
+            timestamp: Local::now(),
+            url: url.to_string(),
+            query: query.to_string(),
+        };
+
+        if let Some(path) = &self.path {
+            let result = path
+                .parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|_| OpenOptions::new().create(true).append(true).open(path))
+                .and_then(|mut file| {
+                    let line = serde_json::to_string(&entry)?;
+                    writeln!(file, "{line}")
+                });
+            if let Err(e) = result {
+                error!("Unable to write the history file: {e}");
+            }
+        }
+        self.entries.push(entry);
+    }
+
+    /// The distinct queries, newest first
+    pub fn queries(&self) -> Vec<&str> {
+        let mut queries: Vec<&str> = Vec::new();
+        for entry in self.entries.iter().rev() {
+            if !queries.contains(&entry.query.as_str()) {
+                queries.push(&entry.query);
+            }
+        }
+        queries
+    }
+
+    /// The distinct queries that fuzzy match `pattern`, newest first
+    pub fn search(&self, pattern: &str) -> Vec<&str> {
+        self.queries()
+            .into_iter()
+            .filter(|query| fuzzy_match(pattern, query))
+            .collect()
+    }
+}
+
+/// True if all chars of `pattern` appear in `text` in the same order, ignoring case
+pub fn fuzzy_match(pattern: &str, text: &str) -> bool {
+    let mut text = text.chars().flat_map(char::to_lowercase);
+    pattern
+        .chars()
+        .flat_map(char::to_lowercase)
+        .filter(|char| !char.is_whitespace())
+        .all(|wanted| text.any(|char| char == wanted))
+}
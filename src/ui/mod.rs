@@ -3,25 +3,52 @@ use std::{
     sync::{Arc, Mutex},
 };
 
+use chrono::{DateTime, Local};
+
 use crossterm::event::{self, Event};
 use ratatui::Frame;
 
-use crate::{loki::Loki, LokiConfig};
+use crate::{
+    loki::{time::TimeRange, Loki, QueryOptions},
+    LokiConfig,
+};
+
+use self::{completion::LabelCache, history::History};
 
+pub mod completion;
+pub mod follow;
+pub mod highlight;
+pub mod history;
+pub mod saved_queries;
 pub mod screen;
 
 pub struct Store {
     pub results: Vec<String>,
     pub results_changed: bool,
+    /// Shown next to the results title, e.g. while following
+    pub status: Option<String>,
+    /// A change to the query, requested by another screen
+    pub query_edit: Option<QueryEdit>,
+}
+
+/// Changes other screens can make to the text of the query screen
+pub enum QueryEdit {
+    /// Insert the text at the cursor
+    Insert(String),
+    /// Replace the whole query
+    Replace(String),
 }
 
 impl Default for Store {
     fn default() -> Self {
         Self {
             results_changed: true,
+            status: None,
+            query_edit: None,
             results: vec![
                 String::from("Type a query above and press enter to see the results"),
                 String::from("You can switch between query and results with ⬆️  and ⬇️."),
+                String::from("While typing a query, ⬆️  and ⬇️  recall earlier ones and ctrl-r searches them."),
                 String::from("Press q or esc to quit"),
             ],
         }
@@ -33,15 +60,35 @@ pub struct App {
     pub loki: Loki,
     pub store: Arc<Mutex<Store>>,
     pub config: LokiConfig,
+    /// Labels for query completion, loaded once per session
+    pub label_cache: Arc<Mutex<LabelCache>>,
+    /// The submitted queries, kept across sessions
+    pub history: History,
+    /// The limit, direction, step and interval of queries
+    pub options: QueryOptions,
+    /// The range all queries and lookups are made for
+    pub time_range: TimeRange,
 }
 
 impl App {
     pub fn new(config: LokiConfig) -> Self {
         Self {
             screens: vec![Box::new(screen::Query::new())],
-            loki: Loki::new(config.loki_url.clone()),
+            loki: config.loki(),
             store: Arc::new(Mutex::new(Store::default())),
             config,
+            label_cache: Arc::default(),
+            history: History::load(),
+            options: QueryOptions::default(),
+            time_range: TimeRange::default(),
+        }
+    }
+
+    /// The resolved time range, `None` lets Loki use its defaults if it can't be resolved
+    pub fn range(&self) -> (Option<DateTime<Local>>, Option<DateTime<Local>>) {
+        match self.time_range.resolve() {
+            Ok((start, end)) => (Some(start), Some(end)),
+            Err(_) => (None, None),
         }
     }
 
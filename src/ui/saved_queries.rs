@@ -0,0 +1,82 @@
+use std::{fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::loki::{logql::parse_duration, time::TimeRange};
+
+/// A named query from the saved queries file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedQuery {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub query: String,
+    /// How far back the query looks by default, e.g. `1h` or `2d`
+    #[serde(default)]
+    pub range: Option<String>,
+    /// The maximum number of lines to return
+    #[serde(default)]
+    pub limit: Option<i64>,
+}
+
+impl SavedQuery {
+    /// The range from `range` ago until now, `None` if no range is set
+    pub fn time_range(&self) -> Option<TimeRange> {
+        self.range.as_deref().map(TimeRange::last)
+    }
+}
+
+///
+/// A library of saved queries, meant to be shared as a file in a team repository.
+/// Written in YAML or TOML, depending on the file extension:
+/// ```toml
+/// [[queries]]
+/// name = "Errors"
+/// description = "All errors of the api"
+/// query = '{job="api"} |= "error"'
+/// range = "1h"
+/// limit = 500
+/// ```
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SavedQueries {
+    #[serde(default)]
+    pub queries: Vec<SavedQuery>,
+}
+
+impl SavedQueries {
+    /// Reads the file at `path`, `.toml` files are read as TOML, everything else as YAML
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Unable to read {}: {e}", path.display()))?;
+        let toml = path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("toml"));
+        let saved = if toml {
+            Self::from_toml(&content)?
+        } else {
+            Self::from_yaml(&content)?
+        };
+        saved.validate()?;
+        Ok(saved)
+    }
+
+    pub fn from_toml(content: &str) -> Result<Self, String> {
+        toml::from_str(content).map_err(|e| format!("Invalid saved queries file: {e}"))
+    }
+
+    pub fn from_yaml(content: &str) -> Result<Self, String> {
+        serde_yaml::from_str(content).map_err(|e| format!("Invalid saved queries file: {e}"))
+    }
+
+    /// Checks that every range can be parsed and used, so mistakes show up when loading
+    fn validate(&self) -> Result<(), String> {
+        for query in &self.queries {
+            if let Some(range) = &query.range {
+                if parse_duration(range).is_none() || TimeRange::last(range).resolve().is_err() {
+                    return Err(format!("Invalid range {range:?} of query {:?}", query.name));
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -2,7 +2,7 @@ use crossterm::event::KeyCode;
 use ratatui::{
     layout::{Alignment, Layout, Rect},
     style::Style,
-    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph, Wrap},
     Frame,
 };
 
@@ -18,7 +18,7 @@ enum Selection {
 pub struct Alert {
     should_close: bool,
     selection: Selection,
-    text: &'static str,
+    text: String,
     title: &'static str,
     action: Option<Box<dyn FnMut()>>,
 }
@@ -26,22 +26,22 @@ pub struct Alert {
 impl Alert {
     pub fn with_action(
         title: &'static str,
-        text: &'static str,
+        text: impl Into<String>,
         action: impl Fn() + 'static,
     ) -> Self {
         Alert {
             title,
-            text,
+            text: text.into(),
             action: Some(Box::from(action)),
             should_close: false,
             selection: Selection::Cancel,
         }
     }
 
-    pub fn new(title: &'static str, text: &'static str) -> Self {
+    pub fn new(title: &'static str, text: impl Into<String>) -> Self {
         Alert {
             title,
-            text,
+            text: text.into(),
             action: None,
             should_close: false,
             selection: Selection::Cancel,
@@ -112,11 +112,14 @@ impl Screen for Alert {
     }
 
     fn render(&mut self, frame: &mut ratatui::prelude::Frame, _app: &crate::ui::App) {
+        // Grows with the text, which has room for 5 lines at the least
+        let height = self.text.lines().count().max(5) as u16 + 5;
         let mut size = frame.size();
-        size.x = size.width / 2 - 25;
-        size.y = size.height / 2 - 5;
+        size.x = (size.width / 2).saturating_sub(25);
+        size.y = (size.height / 2).saturating_sub(height / 2);
         size.width = 50;
-        size.height = 10;
+        size.height = height;
+        let size = size.intersection(frame.size());
         let layout = Layout::default()
             .direction(ratatui::layout::Direction::Vertical)
             .margin(1)
@@ -134,7 +137,10 @@ impl Screen for Alert {
         frame.render_widget(Clear, block.inner(size));
         frame.render_widget(block, size);
 
-        frame.render_widget(Paragraph::new(self.text), layout[0]);
+        frame.render_widget(
+            Paragraph::new(self.text.as_str()).wrap(Wrap { trim: false }),
+            layout[0],
+        );
         self.bottom_buttons(frame, layout[1]);
     }
 
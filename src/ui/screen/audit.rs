@@ -0,0 +1,172 @@
+use chrono::{DateTime, Local};
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::Layout,
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::{
+    loki::audit::{AuditRecord, Operation},
+    ui::App,
+};
+
+use super::Screen;
+
+///
+/// Browses the local audit log of deletes and pushes, newest first.
+/// Shows all details of the selected record, to find out who deleted what.
+pub struct Audit {
+    records: Result<Vec<AuditRecord>, String>,
+    state: ListState,
+    should_close: bool,
+}
+
+impl Audit {
+    pub fn new(app: &App) -> Self {
+        Self {
+            records: Audit::load(app),
+            state: ListState::default().with_selected(Some(0)),
+            should_close: false,
+        }
+    }
+
+    fn load(app: &App) -> Result<Vec<AuditRecord>, String> {
+        let Some(audit_log) = &app.loki.audit_log else {
+            return Err(String::from("There is no audit log"));
+        };
+        match audit_log.records() {
+            Ok(mut records) => {
+                records.reverse();
+                Ok(records)
+            }
+            Err(e) => Err(format!("Unable to read {}: {e}", audit_log.path.display())),
+        }
+    }
+
+    fn selected(&self) -> Option<&AuditRecord> {
+        let records = self.records.as_ref().ok()?;
+        self.state.selected().and_then(|i| records.get(i))
+    }
+
+    fn count(&self) -> usize {
+        self.records.as_ref().map_or(0, Vec::len)
+    }
+
+    fn item(record: &AuditRecord) -> ListItem<'static> {
+        let failed = !record.response.starts_with("accepted");
+        let color = match (record.operation, failed) {
+            (_, true) => Color::Red,
+            (Operation::Delete, false) => Color::Yellow,
+            (Operation::Push, false) => Color::White,
+        };
+        ListItem::new(format!(
+            "{}  {:<6} {:<12} {}",
+            record.timestamp.format("%Y-%m-%d %H:%M:%S"),
+            record.operation.to_string(),
+            record.user,
+            record.query
+        ))
+        .style(Style::default().fg(color))
+    }
+
+    fn details(record: &AuditRecord) -> String {
+        let time = |time: Option<DateTime<Local>>| {
+            time.map_or(String::from("-"), |time| {
+                time.format("%Y-%m-%d %H:%M:%S %:z").to_string()
+            })
+        };
+        format!(
+            "{} by {} at {}\nLoki:     {} (tenant {})\nQuery:    {}\nRange:    {} to {}\nResponse: {}",
+            record.operation,
+            record.user,
+            record.timestamp.format("%Y-%m-%d %H:%M:%S %:z"),
+            record.url,
+            record.tenant.as_deref().unwrap_or("-"),
+            record.query,
+            time(record.start),
+            time(record.end),
+            record.response
+        )
+    }
+}
+
+impl Screen for Audit {
+    fn should_close(&self) -> bool {
+        self.should_close
+    }
+
+    fn render(&mut self, frame: &mut Frame, _app: &App) {
+        let layout = Layout::default()
+            .direction(ratatui::layout::Direction::Vertical)
+            .margin(1)
+            .constraints(
+                [
+                    ratatui::layout::Constraint::Percentage(100),
+                    ratatui::layout::Constraint::Length(9),
+                    ratatui::layout::Constraint::Length(1),
+                ]
+                .as_ref(),
+            )
+            .split(frame.size());
+
+        frame.render_widget(Clear, frame.size());
+        frame.render_widget(
+            Block::default().title("Audit log").borders(Borders::ALL),
+            frame.size(),
+        );
+
+        let items: Vec<ListItem> = match &self.records {
+            Err(error) => vec![ListItem::new(error.clone())],
+            Ok(records) if records.is_empty() => {
+                vec![ListItem::new("No deletes or pushes recorded yet")]
+            }
+            Ok(records) => records.iter().map(Audit::item).collect(),
+        };
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Time, operation, user and query")
+                    .border_style(Style::default().fg(Color::Blue)),
+            )
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, layout[0], &mut self.state);
+
+        let details = self.selected().map(Audit::details).unwrap_or_default();
+        frame.render_widget(
+            Paragraph::new(details)
+                .wrap(Wrap { trim: false })
+                .block(Block::default().borders(Borders::ALL).title("Details")),
+            layout[1],
+        );
+
+        frame.render_widget(
+            Paragraph::new("r: reload ─ q: back").style(Style::default().fg(Color::Gray)),
+            layout[2],
+        );
+    }
+
+    fn handle_key_event(&mut self, key: crossterm::event::KeyEvent, app: &mut App) {
+        match key.code {
+            KeyCode::Up => {
+                let selected = self.state.selected().unwrap_or(0);
+                self.state.select(Some(selected.saturating_sub(1)));
+            }
+            KeyCode::Down => {
+                let selected = self.state.selected().unwrap_or(0);
+                let last = self.count().saturating_sub(1);
+                self.state.select(Some((selected + 1).min(last)));
+            }
+            KeyCode::Char('r') => {
+                self.records = Audit::load(app);
+                self.state.select(Some(0));
+            }
+            KeyCode::Char('q') | KeyCode::Esc => {
+                self.should_close = true;
+            }
+            _ => {}
+        }
+    }
+}
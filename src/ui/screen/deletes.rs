@@ -0,0 +1,243 @@
+use std::{
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::Layout,
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+use crate::{
+    loki::{
+        delete::{DeleteRequest, DeleteStatus},
+        error::Error,
+        Loki,
+    },
+    ui::App,
+};
+
+use super::{alert::Alert, Screen};
+
+enum Loading {
+    Pending,
+    Done(Vec<DeleteRequest>),
+    Failed(String),
+}
+
+///
+/// Lists the delete requests of the tenant with their status.
+/// Pending requests can be cancelled until Loki's grace period ends.
+pub struct Deletes {
+    requests: Arc<Mutex<Loading>>,
+    /// Requests cancelled from here, Loki stops listing them once cancelled
+    cancelled: Arc<Mutex<Vec<DeleteRequest>>>,
+    /// The outcome of the last cancel
+    message: Arc<Mutex<Option<String>>>,
+    state: ListState,
+    should_close: bool,
+}
+
+impl Deletes {
+    pub fn new(app: &App) -> Self {
+        let deletes = Self {
+            requests: Arc::new(Mutex::new(Loading::Pending)),
+            cancelled: Arc::default(),
+            message: Arc::default(),
+            state: ListState::default().with_selected(Some(0)),
+            should_close: false,
+        };
+        deletes.load(app);
+        deletes
+    }
+
+    /// Loads the delete requests in the background, newest first
+    fn load(&self, app: &App) {
+        Deletes::spawn_load(
+            app.loki.clone(),
+            self.requests.clone(),
+            self.cancelled.clone(),
+        );
+    }
+
+    fn spawn_load(
+        mut loki: Loki,
+        requests: Arc<Mutex<Loading>>,
+        cancelled: Arc<Mutex<Vec<DeleteRequest>>>,
+    ) {
+        *requests.lock().unwrap() = Loading::Pending;
+        thread::spawn(move || {
+            let result = loki.delete_requests();
+            *requests.lock().unwrap() = match result {
+                Ok(mut listed) => {
+                    for request in cancelled.lock().unwrap().iter() {
+                        if !listed.iter().any(|r| r.request_id == request.request_id) {
+                            listed.push(request.clone());
+                        }
+                    }
+                    listed.sort_by_key(|request| std::cmp::Reverse(request.created_at));
+                    Loading::Done(listed)
+                }
+                Err(error) => Loading::Failed(error.to_string()),
+            };
+        });
+    }
+
+    fn selected(&self) -> Option<DeleteRequest> {
+        match &*self.requests.lock().unwrap() {
+            Loading::Done(requests) => self.state.selected().and_then(|i| requests.get(i).cloned()),
+            _ => None,
+        }
+    }
+
+    fn count(&self) -> usize {
+        match &*self.requests.lock().unwrap() {
+            Loading::Done(requests) => requests.len(),
+            _ => 0,
+        }
+    }
+
+    /// Asks for confirmation, then cancels the selected request and reloads the list
+    fn cancel(&mut self, app: &mut App) {
+        let Some(request) = self.selected() else {
+            return;
+        };
+        if app.loki.read_only {
+            *self.message.lock().unwrap() = Some(Error::ReadOnly("cancelling deletes").to_string());
+            return;
+        }
+        if request.status != DeleteStatus::Received {
+            *self.message.lock().unwrap() = Some(format!(
+                "Only pending requests can be cancelled, this one is {}",
+                request.status
+            ));
+            return;
+        }
+
+        let loki = app.loki.clone();
+        let requests = self.requests.clone();
+        let cancelled = self.cancelled.clone();
+        let message = self.message.clone();
+        app.screens.push(Box::from(Alert::with_action(
+            "Cancel delete request?",
+            "The data will be kept, if the grace period\nof the request has not ended yet.",
+            move || {
+                let mut loki = loki.clone();
+                let result = loki.cancel_delete(&request.request_id);
+                *message.lock().unwrap() = Some(match result {
+                    Ok(()) => {
+                        let mut request = request.clone();
+                        request.status = DeleteStatus::Cancelled;
+                        cancelled.lock().unwrap().push(request);
+                        String::from("Delete request cancelled")
+                    }
+                    Err(error) => format!("Unable to cancel: {error}"),
+                });
+                Deletes::spawn_load(loki, requests.clone(), cancelled.clone());
+            },
+        )));
+    }
+
+    fn item(request: &DeleteRequest) -> ListItem<'static> {
+        let color = match request.status {
+            DeleteStatus::Received => Color::Yellow,
+            DeleteStatus::Processed => Color::Green,
+            DeleteStatus::Cancelled => Color::DarkGray,
+            DeleteStatus::Partial(_) | DeleteStatus::Other(_) => Color::White,
+        };
+        ListItem::new(format!(
+            "{:<14} {}  {} to {}  {}",
+            request.status.to_string(),
+            request.created_at.format("%Y-%m-%d %H:%M"),
+            request.start.format("%Y-%m-%d %H:%M:%S"),
+            request.end.format("%Y-%m-%d %H:%M:%S"),
+            request.query
+        ))
+        .style(Style::default().fg(color))
+    }
+}
+
+impl Screen for Deletes {
+    fn should_close(&self) -> bool {
+        self.should_close
+    }
+
+    fn render(&mut self, frame: &mut Frame, _app: &App) {
+        let layout = Layout::default()
+            .direction(ratatui::layout::Direction::Vertical)
+            .margin(1)
+            .constraints(
+                [
+                    ratatui::layout::Constraint::Length(1),
+                    ratatui::layout::Constraint::Percentage(100),
+                    ratatui::layout::Constraint::Length(1),
+                ]
+                .as_ref(),
+            )
+            .split(frame.size());
+
+        frame.render_widget(Clear, frame.size());
+        frame.render_widget(
+            Block::default()
+                .title("Delete requests")
+                .borders(Borders::ALL),
+            frame.size(),
+        );
+
+        let message = self.message.lock().unwrap().clone().unwrap_or_default();
+        frame.render_widget(Paragraph::new(message), layout[0]);
+
+        let items: Vec<ListItem> = match &*self.requests.lock().unwrap() {
+            Loading::Pending => vec![ListItem::new("Loading...")],
+            Loading::Failed(error) => error
+                .lines()
+                .map(|line| ListItem::new(line.to_string()))
+                .collect(),
+            Loading::Done(requests) if requests.is_empty() => {
+                vec![ListItem::new("No delete requests")]
+            }
+            Loading::Done(requests) => requests.iter().map(Deletes::item).collect(),
+        };
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Status, created, range and query")
+                    .border_style(Style::default().fg(Color::Blue)),
+            )
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, layout[1], &mut self.state);
+
+        frame.render_widget(
+            Paragraph::new("c: cancel ─ r: reload ─ q: back")
+                .style(Style::default().fg(Color::Gray)),
+            layout[2],
+        );
+    }
+
+    fn handle_key_event(&mut self, key: crossterm::event::KeyEvent, app: &mut App) {
+        match key.code {
+            KeyCode::Up => {
+                let selected = self.state.selected().unwrap_or(0);
+                self.state.select(Some(selected.saturating_sub(1)));
+            }
+            KeyCode::Down => {
+                let selected = self.state.selected().unwrap_or(0);
+                let last = self.count().saturating_sub(1);
+                self.state.select(Some((selected + 1).min(last)));
+            }
+            KeyCode::Char('c') => self.cancel(app),
+            KeyCode::Char('r') => {
+                *self.message.lock().unwrap() = None;
+                self.load(app);
+            }
+            KeyCode::Char('q') | KeyCode::Esc => {
+                self.should_close = true;
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Layout, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState},
+    Frame,
+};
+use tui_textarea::TextArea;
+
+use crate::ui::{App, QueryEdit};
+
+use super::Screen;
+
+///
+/// A popup to search the query history.
+/// The picked query replaces the one in the query screen.
+pub struct HistorySearch<'a> {
+    search_textarea: TextArea<'a>,
+    state: ListState,
+    should_close: bool,
+}
+
+impl HistorySearch<'_> {
+    pub fn new() -> Self {
+        let mut search_textarea = TextArea::default();
+        search_textarea.set_cursor_line_style(Style::default());
+        search_textarea.set_placeholder_text("Type to search");
+        Self {
+            search_textarea,
+            state: ListState::default().with_selected(Some(0)),
+            should_close: false,
+        }
+    }
+
+    fn matches<'b>(&self, app: &'b App) -> Vec<&'b str> {
+        app.history.search(&self.search_textarea.lines()[0])
+    }
+}
+
+impl Default for HistorySearch<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Screen for HistorySearch<'_> {
+    fn should_close(&self) -> bool {
+        self.should_close
+    }
+
+    fn render(&mut self, frame: &mut Frame, app: &App) {
+        let size = frame.size();
+        let width = size.width.saturating_sub(8).min(100);
+        let height = size.height.saturating_sub(4).min(20);
+        let area = Rect::new(
+            (size.width - width) / 2,
+            (size.height - height) / 2,
+            width,
+            height,
+        );
+        let layout = Layout::default()
+            .direction(ratatui::layout::Direction::Vertical)
+            .margin(1)
+            .constraints(
+                [
+                    ratatui::layout::Constraint::Length(3),
+                    ratatui::layout::Constraint::Percentage(100),
+                ]
+                .as_ref(),
+            )
+            .split(area);
+
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Block::default().title("History").borders(Borders::ALL),
+            area,
+        );
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title("Search")
+            .border_style(Style::default().fg(Color::Yellow));
+        let inner_area = block.inner(layout[0]);
+        frame.render_widget(block, layout[0]);
+        frame.render_widget(self.search_textarea.widget(), inner_area);
+
+        let matches = self.matches(app);
+        let items: Vec<ListItem> = if matches.is_empty() {
+            vec![ListItem::new("No matching queries")]
+        } else {
+            matches.iter().map(|query| ListItem::new(*query)).collect()
+        };
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title("Queries"))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, layout[1], &mut self.state);
+    }
+
+    fn handle_key_event(&mut self, key: crossterm::event::KeyEvent, app: &mut App) {
+        match key.code {
+            KeyCode::Esc => {
+                self.should_close = true;
+            }
+            KeyCode::Up => {
+                let selected = self.state.selected().unwrap_or(0);
+                self.state.select(Some(selected.saturating_sub(1)));
+            }
+            KeyCode::Down => {
+                let selected = self.state.selected().unwrap_or(0);
+                let last = self.matches(app).len().saturating_sub(1);
+                self.state.select(Some((selected + 1).min(last)));
+            }
+            KeyCode::Enter => {
+                let selected = self.state.selected().unwrap_or(0);
+                if let Some(query) = self.matches(app).get(selected) {
+                    app.store.lock().unwrap().query_edit =
+                        Some(QueryEdit::Replace(query.to_string()));
+                }
+                self.should_close = true;
+            }
+            _ => {
+                self.search_textarea.input(key);
+                self.state.select(Some(0));
+            }
+        }
+    }
+}
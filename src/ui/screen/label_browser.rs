@@ -0,0 +1,329 @@
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Layout, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+use crate::{
+    loki::labels::escape,
+    ui::{App, QueryEdit},
+};
+
+use super::Screen;
+
+enum Loading {
+    Pending,
+    Done(Vec<String>),
+    Failed(String),
+}
+
+enum View {
+    Labels,
+    /// The values of the label with the given name
+    Values(String),
+}
+
+///
+/// Lists the labels Loki knows about and lets the user pick values for them.
+/// The picked values are combined into a stream selector for the query screen.
+pub struct LabelBrowser {
+    view: View,
+    items: Arc<Mutex<Loading>>,
+    state: ListState,
+    /// The values checked in the current values view
+    checked: HashSet<String>,
+    /// The picked label values, in the order the labels were picked
+    matchers: Vec<(String, Vec<String>)>,
+    should_close: bool,
+}
+
+impl LabelBrowser {
+    pub fn new(app: &App) -> Self {
+        let browser = Self {
+            view: View::Labels,
+            items: Arc::new(Mutex::new(Loading::Pending)),
+            state: ListState::default().with_selected(Some(0)),
+            checked: HashSet::new(),
+            matchers: Vec::new(),
+            should_close: false,
+        };
+        browser.load(app);
+        browser
+    }
+
+    /// Loads the labels or values for the current view in the background
+    fn load(&self, app: &App) {
+        let items = self.items.clone();
+        *items.lock().unwrap() = Loading::Pending;
+        let mut loki = app.loki.clone();
+        let (start, end) = app.range();
+
+        match &self.view {
+            View::Labels => {
+                thread::spawn(move || {
+                    let labels = loki.labels(start, end);
+                    *items.lock().unwrap() = match labels {
+                        Ok(labels) => Loading::Done(labels),
+                        Err(error) => Loading::Failed(format!("Unable to load labels: {error}")),
+                    };
+                });
+            }
+            View::Values(label) => {
+                // Only offer values that still match the other labels picked so far,
+                // the values picked for this label are kept open to extend them
+                let selector = self.selector_without(Some(label));
+                let label = label.clone();
+                thread::spawn(move || {
+                    let query = selector.as_deref();
+                    let values = loki.label_values(&label, start, end, query);
+                    *items.lock().unwrap() = match values {
+                        Ok(values) => Loading::Done(values),
+                        Err(error) => {
+                            Loading::Failed(format!("Unable to load values for {label}: {error}"))
+                        }
+                    };
+                });
+            }
+        }
+    }
+
+    /// The stream selector for the picked values, if any were picked
+    fn selector(&self) -> Option<String> {
+        self.selector_without(None)
+    }
+
+    /// The stream selector for the picked values of all labels but `skip`
+    fn selector_without(&self, skip: Option<&str>) -> Option<String> {
+        let matchers: Vec<_> = self
+            .matchers
+            .iter()
+            .filter(|(label, _)| Some(label.as_str()) != skip)
+            .map(|(label, values)| match values.as_slice() {
+                [value] => format!("{label}=\"{}\"", escape(value)),
+                values => {
+                    let values: Vec<_> = values.iter().map(|value| escape_regex(value)).collect();
+                    format!("{label}=~\"{}\"", escape(&values.join("|")))
+                }
+            })
+            .collect();
+        if matchers.is_empty() {
+            return None;
+        }
+        Some(format!("{{{}}}", matchers.join(", ")))
+    }
+
+    fn selected_item(&self) -> Option<String> {
+        match &*self.items.lock().unwrap() {
+            Loading::Done(items) => self.state.selected().and_then(|i| items.get(i).cloned()),
+            _ => None,
+        }
+    }
+
+    fn item_count(&self) -> usize {
+        match &*self.items.lock().unwrap() {
+            Loading::Done(items) => items.len(),
+            _ => 0,
+        }
+    }
+
+    fn open(&mut self, view: View, app: &App) {
+        self.checked = match &view {
+            View::Values(label) => self
+                .matchers
+                .iter()
+                .find(|(picked, _)| picked == label)
+                .map(|(_, values)| values.iter().cloned().collect())
+                .unwrap_or_default(),
+            View::Labels => HashSet::new(),
+        };
+        self.view = view;
+        self.state.select(Some(0));
+        self.load(app);
+    }
+
+    fn selector_bar(&self, frame: &mut Frame, rect: Rect) {
+        let block = Block::default().borders(Borders::ALL).title("Selector");
+        let selector = self
+            .selector()
+            .unwrap_or_else(|| String::from("Pick a label to start"));
+        frame.render_widget(Paragraph::new(selector).block(block), rect);
+    }
+
+    fn list(&mut self, frame: &mut Frame, rect: Rect) {
+        let title = match &self.view {
+            View::Labels => String::from("Labels"),
+            View::Values(label) => format!("Values of {label}"),
+        };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(title)
+            .border_style(Style::default().fg(Color::Blue));
+
+        let items: Vec<ListItem> = match &*self.items.lock().unwrap() {
+            Loading::Pending => vec![ListItem::new("Loading...")],
+            Loading::Failed(error) => vec![ListItem::new(error.clone())],
+            Loading::Done(items) if items.is_empty() => vec![ListItem::new("Nothing found")],
+            Loading::Done(items) => items
+                .iter()
+                .map(|item| {
+                    let picked = match &self.view {
+                        View::Labels => self.matchers.iter().any(|(label, _)| label == item),
+                        View::Values(_) => self.checked.contains(item),
+                    };
+                    let marker = if picked { "[x] " } else { "[ ] " };
+                    ListItem::new(format!("{marker}{item}"))
+                })
+                .collect(),
+        };
+
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, rect, &mut self.state);
+    }
+
+    fn keyhints(&self, frame: &mut Frame, rect: Rect) {
+        let hints = match self.view {
+            View::Labels => {
+                "enter: values ─ i: insert selector ─ backspace: drop last label ─ q: quit"
+            }
+            View::Values(_) => "space: pick ─ enter: apply ─ esc: back",
+        };
+        frame.render_widget(
+            Paragraph::new(hints).style(Style::default().fg(Color::Gray)),
+            rect,
+        );
+    }
+}
+
+impl Screen for LabelBrowser {
+    fn should_close(&self) -> bool {
+        self.should_close
+    }
+
+    fn render(&mut self, frame: &mut Frame, _app: &App) {
+        let layout = Layout::default()
+            .direction(ratatui::layout::Direction::Vertical)
+            .margin(1)
+            .constraints(
+                [
+                    ratatui::layout::Constraint::Length(3),
+                    ratatui::layout::Constraint::Percentage(100),
+                    ratatui::layout::Constraint::Length(1),
+                ]
+                .as_ref(),
+            )
+            .split(frame.size());
+
+        frame.render_widget(Clear, frame.size());
+        frame.render_widget(
+            Block::default()
+                .title("Label browser")
+                .borders(Borders::ALL),
+            frame.size(),
+        );
+
+        self.selector_bar(frame, layout[0]);
+        self.list(frame, layout[1]);
+        self.keyhints(frame, layout[2]);
+    }
+
+    fn handle_key_event(&mut self, key: crossterm::event::KeyEvent, app: &mut App) {
+        match key.code {
+            KeyCode::Up => {
+                let selected = self.state.selected().unwrap_or(0);
+                self.state.select(Some(selected.saturating_sub(1)));
+                return;
+            }
+            KeyCode::Down => {
+                let selected = self.state.selected().unwrap_or(0);
+                let last = self.item_count().saturating_sub(1);
+                self.state.select(Some((selected + 1).min(last)));
+                return;
+            }
+            _ => {}
+        }
+
+        match &self.view {
+            View::Labels => match key.code {
+                KeyCode::Enter => {
+                    if let Some(label) = self.selected_item() {
+                        self.open(View::Values(label), app);
+                    }
+                }
+                KeyCode::Backspace => {
+                    self.matchers.pop();
+                }
+                KeyCode::Char('i') => {
+                    if let Some(selector) = self.selector() {
+                        app.store.lock().unwrap().query_edit = Some(QueryEdit::Insert(selector));
+                    }
+                    self.should_close = true;
+                }
+                KeyCode::Char('q') | KeyCode::Esc => {
+                    self.should_close = true;
+                }
+                _ => {}
+            },
+            View::Values(label) => match key.code {
+                KeyCode::Char(' ') => {
+                    if let Some(value) = self.selected_item() {
+                        if !self.checked.remove(&value) {
+                            self.checked.insert(value);
+                        }
+                    }
+                }
+                KeyCode::Enter => {
+                    let label = label.clone();
+                    // Keep the order in which the values were listed by Loki
+                    let values: Vec<String> = match &*self.items.lock().unwrap() {
+                        Loading::Done(items) => items
+                            .iter()
+                            .filter(|item| self.checked.contains(*item))
+                            .cloned()
+                            .collect(),
+                        _ => Vec::new(),
+                    };
+                    match self
+                        .matchers
+                        .iter_mut()
+                        .find(|(picked, _)| *picked == label)
+                    {
+                        Some(matcher) if values.is_empty() => {
+                            let label = matcher.0.clone();
+                            self.matchers.retain(|(picked, _)| *picked != label);
+                        }
+                        Some(matcher) => matcher.1 = values,
+                        None if values.is_empty() => {}
+                        None => self.matchers.push((label, values)),
+                    }
+                    self.open(View::Labels, app);
+                }
+                KeyCode::Esc | KeyCode::Char('q') => {
+                    self.open(View::Labels, app);
+                }
+                _ => {}
+            },
+        }
+    }
+}
+
+/// Escapes the characters that have a special meaning in regular expressions
+fn escape_regex(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for char in value.chars() {
+        if "\\.+*?()|[]{}^$".contains(char) {
+            escaped.push('\\');
+        }
+        escaped.push(char);
+    }
+    escaped
+}
@@ -2,9 +2,18 @@ use crossterm::event::KeyEvent;
 use ratatui::Frame;
 
 mod alert;
+mod audit;
+mod deletes;
+mod history_search;
+mod label_browser;
+mod options;
+mod profiles;
 mod query;
 mod remove;
+mod saved_queries;
+mod series;
 mod settings;
+mod time_range;
 
 pub use alert::Alert;
 pub use query::Query;
@@ -0,0 +1,205 @@
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Style},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+use tui_textarea::TextArea;
+
+use crate::{
+    loki::{
+        logql::{format_duration, parse_duration},
+        Direction, QueryOptions,
+    },
+    ui::App,
+};
+
+use super::Screen;
+
+const LIMIT: usize = 0;
+const DIRECTION: usize = 1;
+const STEP: usize = 2;
+const INTERVAL: usize = 3;
+const TITLES: [&str; 4] = [
+    "Limit (lines)",
+    "Direction",
+    "Step (e.g. 1m, metric queries)",
+    "Interval (e.g. 30s, log queries)",
+];
+
+///
+/// Edits the options sent with every range query.
+/// Empty fields leave the choice to Loki, invalid ones are not taken over.
+pub struct Options<'a> {
+    textareas: [TextArea<'a>; 4],
+    direction: Direction,
+    selected: usize,
+    editing: bool,
+    should_close: bool,
+}
+
+impl Options<'_> {
+    pub fn new(options: &QueryOptions) -> Self {
+        let textarea = |value: Option<String>| {
+            let mut textarea = TextArea::new(vec![value.unwrap_or_default()]);
+            textarea.set_cursor_line_style(Style::default());
+            textarea
+        };
+        Self {
+            textareas: [
+                textarea(options.limit.map(|limit| limit.to_string())),
+                // The direction is toggled, not typed
+                textarea(None),
+                textarea(options.step.map(format_duration)),
+                textarea(options.interval.map(format_duration)),
+            ],
+            direction: options.direction,
+            selected: 0,
+            editing: false,
+            should_close: false,
+        }
+    }
+
+    fn text(&self, field: usize) -> &str {
+        self.textareas[field].lines()[0].trim()
+    }
+
+    /// The parsed value of a field, `Err` if it can't be parsed
+    fn limit(&self) -> Result<Option<i64>, ()> {
+        match self.text(LIMIT) {
+            "" => Ok(None),
+            text => text
+                .parse::<i64>()
+                .ok()
+                .filter(|limit| *limit > 0)
+                .map(Some)
+                .ok_or(()),
+        }
+    }
+
+    fn duration(&self, field: usize) -> Result<Option<std::time::Duration>, ()> {
+        match self.text(field) {
+            "" => Ok(None),
+            text => parse_duration(text)
+                .filter(|duration| !duration.is_zero())
+                .map(Some)
+                .ok_or(()),
+        }
+    }
+
+    fn is_valid(&self, field: usize) -> bool {
+        match field {
+            LIMIT => self.limit().is_ok(),
+            STEP | INTERVAL => self.duration(field).is_ok(),
+            _ => true,
+        }
+    }
+
+    /// Takes over all valid fields
+    fn save(&self, app: &mut App) {
+        if let Ok(limit) = self.limit() {
+            app.options.limit = limit;
+        }
+        app.options.direction = self.direction;
+        if let Ok(step) = self.duration(STEP) {
+            app.options.step = step;
+        }
+        if let Ok(interval) = self.duration(INTERVAL) {
+            app.options.interval = interval;
+        }
+    }
+}
+
+impl Screen for Options<'_> {
+    fn should_close(&self) -> bool {
+        self.should_close
+    }
+
+    fn render(&mut self, frame: &mut Frame, _app: &App) {
+        let mut area = Rect::new(0, 0, 50, 3 * TITLES.len() as u16 + 3);
+        area.x = (frame.size().width / 2).saturating_sub(area.width / 2);
+        area.y = (frame.size().height / 2).saturating_sub(area.height / 2);
+        let area = area.intersection(frame.size());
+
+        let block = Block::default()
+            .title("Query options")
+            .borders(Borders::ALL);
+        let inner = block.inner(area);
+        frame.render_widget(Clear, area);
+        frame.render_widget(block, area);
+
+        let mut constraints: Vec<Constraint> =
+            TITLES.iter().map(|_| Constraint::Length(3)).collect();
+        constraints.push(Constraint::Length(1));
+        let layout = Layout::default()
+            .direction(ratatui::layout::Direction::Vertical)
+            .constraints(constraints)
+            .split(inner);
+
+        for (i, title) in TITLES.iter().enumerate() {
+            let color = match (i == self.selected, self.editing, self.is_valid(i)) {
+                (_, _, false) => Color::Red,
+                (true, true, _) => Color::Yellow,
+                (true, false, _) => Color::Blue,
+                _ => Color::White,
+            };
+            let block = Block::default()
+                .borders(Borders::ALL)
+                .title(title.to_string())
+                .border_style(Style::default().fg(color));
+            if i == DIRECTION {
+                let direction = match self.direction {
+                    Direction::Backward => "backward (newest first)",
+                    Direction::Forward => "forward (oldest first)",
+                };
+                frame.render_widget(Paragraph::new(direction), block.inner(layout[i]));
+            } else {
+                frame.render_widget(self.textareas[i].widget(), block.inner(layout[i]));
+            }
+            frame.render_widget(block, layout[i]);
+        }
+
+        frame.render_widget(
+            Paragraph::new("enter: edit / toggle ─ esc: apply and close")
+                .style(Style::default().fg(Color::Gray)),
+            layout[TITLES.len()],
+        );
+    }
+
+    fn handle_key_event(&mut self, key: crossterm::event::KeyEvent, app: &mut App) {
+        if self.editing {
+            match key.code {
+                crossterm::event::KeyCode::Esc | crossterm::event::KeyCode::Enter => {
+                    self.editing = false;
+                }
+                _ => {
+                    self.textareas[self.selected].input(key);
+                }
+            }
+            return;
+        }
+
+        match key.code {
+            crossterm::event::KeyCode::Esc | crossterm::event::KeyCode::Char('q') => {
+                self.save(app);
+                self.should_close = true;
+            }
+            crossterm::event::KeyCode::Up => {
+                self.selected = self.selected.saturating_sub(1);
+            }
+            crossterm::event::KeyCode::Down => {
+                self.selected = (self.selected + 1).min(TITLES.len() - 1);
+            }
+            crossterm::event::KeyCode::Enter if self.selected == DIRECTION => {
+                self.direction = match self.direction {
+                    Direction::Backward => Direction::Forward,
+                    Direction::Forward => Direction::Backward,
+                };
+            }
+            crossterm::event::KeyCode::Enter => {
+                self.editing = true;
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,229 @@
+use std::{path::Path, sync::Arc};
+
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::Layout,
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+use tui_textarea::TextArea;
+
+use crate::{
+    profile::{load_grafana_datasources, Profile},
+    ui::App,
+};
+
+use super::Screen;
+
+#[derive(PartialEq)]
+enum Selection {
+    Profiles,
+    /// The path of a Grafana datasource file to import, true while editing
+    Import(bool),
+}
+
+///
+/// Lists the profiles of the config and switches between them.
+/// Profiles can be imported from a Grafana datasource provisioning file.
+pub struct Profiles<'a> {
+    state: ListState,
+    path_textarea: TextArea<'a>,
+    selection: Selection,
+    /// The outcome of the last import
+    message: Option<String>,
+    should_close: bool,
+}
+
+impl Profiles<'_> {
+    pub fn new(app: &App) -> Self {
+        let active = app
+            .config
+            .active_profile
+            .as_ref()
+            .and_then(|name| app.config.profiles.iter().position(|p| &p.name == name));
+        let mut path_textarea = TextArea::default();
+        path_textarea.set_cursor_line_style(Style::default());
+        Self {
+            state: ListState::default().with_selected(Some(active.unwrap_or(0))),
+            path_textarea,
+            selection: Selection::Profiles,
+            message: None,
+            should_close: false,
+        }
+    }
+
+    /// Switches to the selected profile and recreates the client
+    fn switch(&mut self, app: &mut App) {
+        let Some(profile) = self
+            .state
+            .selected()
+            .and_then(|i| app.config.profiles.get(i))
+        else {
+            return;
+        };
+        let name = profile.name.clone();
+        if let Err(error) = app.config.use_profile(&name) {
+            self.message = Some(error);
+            return;
+        }
+        app.loki = app.config.loki();
+        // The labels differ between Lokis
+        app.label_cache = Arc::default();
+        self.should_close = true;
+    }
+
+    fn import(&mut self, app: &mut App) {
+        let path = self.path_textarea.lines()[0].trim().to_string();
+        self.message = Some(match load_grafana_datasources(Path::new(&path)) {
+            Ok(profiles) => {
+                let names: Vec<String> = profiles.iter().map(|p| p.name.clone()).collect();
+                app.config.import_profiles(profiles);
+                self.selection = Selection::Profiles;
+                format!("Imported {}", names.join(", "))
+            }
+            Err(error) => error,
+        });
+    }
+
+    fn item(profile: &Profile, active: bool) -> ListItem<'static> {
+        let mut details = vec![profile.loki_url.clone()];
+        if let Some(tenant) = &profile.loki_tenant_id {
+            details.push(format!("tenant {tenant}"));
+        }
+        if profile.read_only {
+            details.push(String::from("read-only"));
+        }
+        let marker = if active { "*" } else { " " };
+        let style = match active {
+            true => Style::default().fg(Color::Green),
+            false => Style::default(),
+        };
+        ListItem::new(format!(
+            "{marker} {:<16} {}",
+            profile.name,
+            details.join(", ")
+        ))
+        .style(style)
+    }
+}
+
+impl Screen for Profiles<'_> {
+    fn should_close(&self) -> bool {
+        self.should_close
+    }
+
+    fn render(&mut self, frame: &mut Frame, app: &App) {
+        let layout = Layout::default()
+            .direction(ratatui::layout::Direction::Vertical)
+            .margin(1)
+            .constraints(
+                [
+                    ratatui::layout::Constraint::Percentage(100),
+                    ratatui::layout::Constraint::Length(3),
+                    ratatui::layout::Constraint::Length(1),
+                    ratatui::layout::Constraint::Length(1),
+                ]
+                .as_ref(),
+            )
+            .split(frame.size());
+
+        frame.render_widget(Clear, frame.size());
+        frame.render_widget(
+            Block::default().title("Profiles").borders(Borders::ALL),
+            frame.size(),
+        );
+
+        let items: Vec<ListItem> = match app.config.profiles.is_empty() {
+            true => vec![ListItem::new(
+                "No profiles, add them to the config or import Grafana datasources",
+            )],
+            false => app
+                .config
+                .profiles
+                .iter()
+                .map(|profile| {
+                    let active = app.config.active_profile.as_ref() == Some(&profile.name);
+                    Profiles::item(profile, active)
+                })
+                .collect(),
+        };
+        let list_color = match self.selection {
+            Selection::Profiles => Color::Blue,
+            Selection::Import(_) => Color::White,
+        };
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Name, URL and tenant")
+                    .border_style(Style::default().fg(list_color)),
+            )
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, layout[0], &mut self.state);
+
+        let path_color = match self.selection {
+            Selection::Import(true) => Color::Yellow,
+            Selection::Import(false) => Color::Blue,
+            Selection::Profiles => Color::White,
+        };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title("Import Grafana datasource file, e.g. loki.yaml")
+            .border_style(Style::default().fg(path_color));
+        frame.render_widget(self.path_textarea.widget(), block.inner(layout[1]));
+        frame.render_widget(block, layout[1]);
+
+        frame.render_widget(
+            Paragraph::new(self.message.clone().unwrap_or_default()),
+            layout[2],
+        );
+        frame.render_widget(
+            Paragraph::new("enter: switch / import ─ i: import ─ q: back")
+                .style(Style::default().fg(Color::Gray)),
+            layout[3],
+        );
+    }
+
+    fn handle_key_event(&mut self, key: crossterm::event::KeyEvent, app: &mut App) {
+        if self.selection == Selection::Import(true) {
+            match key.code {
+                KeyCode::Esc => self.selection = Selection::Import(false),
+                KeyCode::Enter => self.import(app),
+                _ => {
+                    self.path_textarea.input(key);
+                }
+            }
+            return;
+        }
+
+        match key.code {
+            KeyCode::Up => match self.selection {
+                Selection::Import(_) => self.selection = Selection::Profiles,
+                Selection::Profiles => {
+                    let selected = self.state.selected().unwrap_or(0);
+                    self.state.select(Some(selected.saturating_sub(1)));
+                }
+            },
+            KeyCode::Down => match self.selection {
+                Selection::Profiles => {
+                    let selected = self.state.selected().unwrap_or(0);
+                    match selected + 1 < app.config.profiles.len() {
+                        true => self.state.select(Some(selected + 1)),
+                        false => self.selection = Selection::Import(false),
+                    }
+                }
+                Selection::Import(_) => {}
+            },
+            KeyCode::Char('i') => self.selection = Selection::Import(true),
+            KeyCode::Enter => match self.selection {
+                Selection::Profiles => self.switch(app),
+                Selection::Import(_) => self.selection = Selection::Import(true),
+            },
+            KeyCode::Char('q') | KeyCode::Esc => {
+                self.should_close = true;
+            }
+            _ => {}
+        }
+    }
+}
@@ -1,23 +1,39 @@
-use std::{collections::HashMap, thread, vec};
+use std::{collections::HashMap, fmt::Display, thread, vec};
 
-use crossterm::event::KeyEvent;
+use crossterm::event::{KeyEvent, KeyModifiers};
 use log::info;
 
 use ratatui::{
     layout::{Layout, Rect},
-    style::{Color, Style},
+    style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::Paragraph,
+    widgets::{Clear, List, ListItem, ListState, Paragraph},
     Frame,
 };
 
 #[cfg(feature = "debug")]
 use tui_logger::{TuiLoggerLevelOutput, TuiLoggerSmartWidget};
-use tui_textarea::TextArea;
+use tui_textarea::{CursorMove, TextArea};
 
-use crate::ui::App;
+use crate::{
+    loki::{
+        logql::{self, format_duration, ParseError},
+        paging::PagedResult,
+        result::LokiResult,
+        Direction,
+    },
+    ui::{
+        completion::{function_keywords, Context, Kind, LabelCache, PIPELINE_KEYWORDS},
+        follow::{format_labels, Follow},
+        highlight, App, QueryEdit, Store,
+    },
+};
 
-use super::{remove::Remove, settings::Settings, Screen};
+use super::{
+    alert::Alert, audit::Audit, history_search::HistorySearch, label_browser::LabelBrowser,
+    options::Options, profiles::Profiles, remove::Remove, saved_queries::SavedQueriesPicker,
+    series::Series, settings::Settings, time_range::TimeRangePicker, Screen,
+};
 
 use ratatui::widgets::{Block, Borders};
 
@@ -27,10 +43,46 @@ enum Selection {
     Results(bool),
 }
 
+/// Whether the query is evaluated over a time range or at a single point in time
+#[derive(Clone, Copy, PartialEq)]
+enum Mode {
+    Range,
+    Instant,
+}
+
+impl Display for Mode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Mode::Range => write!(f, "range"),
+            Mode::Instant => write!(f, "instant"),
+        }
+    }
+}
+
+/// The completion popup below the query bar
+struct Completion {
+    context: Context,
+    items: Vec<String>,
+    selected: usize,
+    /// Opened with Tab, which keeps it open for an empty word
+    explicit: bool,
+}
+
 pub struct Query<'a> {
     query_textarea: TextArea<'a>,
+    completion: Option<Completion>,
     results_textarea: TextArea<'a>,
     selection: Selection,
+    mode: Mode,
+    /// Whether range queries keep reading pages past the limit
+    paging: bool,
+    follow: Option<Follow>,
+    /// How far the highlighted query is scrolled to the right
+    query_scroll: u16,
+    /// The index into the history queries while recalling them with up and down
+    history_position: Option<usize>,
+    /// The query that was being typed before recalling from the history
+    draft: String,
     should_close: bool,
 }
 
@@ -44,13 +96,19 @@ impl Query<'_> {
     pub fn new() -> Self {
         let mut query_textarea = TextArea::default();
         query_textarea.set_cursor_line_style(Style::default());
-        query_textarea.set_placeholder_text("Enter a valid query");
         let mut results_textarea = TextArea::default();
         results_textarea.set_cursor_line_style(Style::default());
         Self {
             results_textarea,
             query_textarea,
+            completion: None,
             selection: Selection::Query(false),
+            mode: Mode::Range,
+            paging: false,
+            follow: None,
+            query_scroll: 0,
+            history_position: None,
+            draft: String::new(),
             should_close: false,
         }
     }
@@ -61,8 +119,21 @@ impl Query<'_> {
         keymap.insert('q', String::from("quit"));
         keymap.insert('s', String::from("settings"));
         keymap.insert('d', String::from("delete"));
+        keymap.insert('m', String::from("mode"));
+        keymap.insert('f', String::from("follow"));
+        keymap.insert('p', String::from("pause"));
+        keymap.insert('e', String::from("series explorer"));
+        keymap.insert('l', String::from("labels"));
+        keymap.insert('v', String::from("saved"));
+        keymap.insert('t', String::from("time"));
+        keymap.insert('g', String::from("paging"));
+        keymap.insert('o', String::from("options"));
+        keymap.insert('a', String::from("audit"));
+        keymap.insert('r', String::from("profiles"));
         // quick hack to get the keys in the right order
-        let keys = vec!['q', 's', 'd'];
+        let keys = vec![
+            'q', 's', 'd', 'm', 'f', 'p', 'e', 'l', 'v', 't', 'g', 'o', 'a', 'r',
+        ];
 
         let mut text = Line::from("");
         for key in keys {
@@ -83,7 +154,19 @@ impl Query<'_> {
         frame.render_widget(Paragraph::new(text), rect);
     }
 
-    fn query_bar(&self, frame: &mut Frame, rect: Rect) {
+    fn query_bar(&mut self, frame: &mut Frame, rect: Rect, app: &App) {
+        if let Some(edit) = app.store.lock().unwrap().query_edit.take() {
+            match edit {
+                QueryEdit::Insert(text) => {
+                    self.query_textarea.insert_str(text);
+                }
+                QueryEdit::Replace(text) => {
+                    self.history_position = None;
+                    self.set_query(text);
+                }
+            }
+        }
+
         let color = match self.selection {
             Selection::Query(true) => ratatui::style::Color::Yellow,
             Selection::Query(false) => ratatui::style::Color::Blue,
@@ -92,11 +175,293 @@ impl Query<'_> {
 
         let block = Block::default()
             .borders(Borders::ALL)
-            .title("Query")
+            .title(Query::title(self.mode, self.paging, app))
             .border_style(Style::default().fg(color));
         let inner_area = block.inner(rect);
         frame.render_widget(block, rect);
-        frame.render_widget(self.query_textarea.widget(), inner_area);
+        let (paragraph, scroll) = highlight::query_paragraph(
+            &self.query_textarea,
+            "Enter a valid query",
+            inner_area.width,
+        );
+        self.query_scroll = scroll;
+        frame.render_widget(paragraph, inner_area);
+    }
+
+    /// The query bar title, with the mode, the time range, any non default options and paging
+    fn title(mode: Mode, paging: bool, app: &App) -> String {
+        let mut details = vec![mode.to_string(), app.time_range.to_string()];
+        if let Some(limit) = app.options.limit {
+            details.push(format!("limit {limit}"));
+        }
+        if app.options.direction == Direction::Forward {
+            details.push(String::from("forward"));
+        }
+        if let Some(step) = app.options.step {
+            details.push(format!("step {}", format_duration(step)));
+        }
+        if let Some(interval) = app.options.interval {
+            details.push(format!("interval {}", format_duration(interval)));
+        }
+        if paging && mode == Mode::Range {
+            details.push(format!("paging up to {} lines", app.config.max_lines));
+        }
+        if app.loki.read_only {
+            details.push(String::from("read-only"));
+        }
+        match &app.config.active_profile {
+            Some(profile) => format!("Query [{profile}] ({})", details.join(", ")),
+            None => format!("Query ({})", details.join(", ")),
+        }
+    }
+
+    /// Replaces the query and puts the cursor at its end
+    fn set_query(&mut self, text: String) {
+        self.query_textarea = TextArea::new(vec![text]);
+        self.query_textarea.set_cursor_line_style(Style::default());
+        self.query_textarea.move_cursor(CursorMove::End);
+    }
+
+    /// Steps through the history, `older` is true for the up key
+    fn recall(&mut self, app: &App, older: bool) {
+        let queries = app.history.queries();
+        let position = match (self.history_position, older) {
+            (None, true) if !queries.is_empty() => {
+                self.draft = self.query_textarea.lines()[0].clone();
+                Some(0)
+            }
+            (None, _) => return,
+            (Some(position), true) => Some((position + 1).min(queries.len() - 1)),
+            (Some(0), false) => None,
+            (Some(position), false) => Some(position - 1),
+        };
+        self.history_position = position;
+        let text = match position {
+            Some(position) => queries[position].to_string(),
+            None => self.draft.clone(),
+        };
+        self.set_query(text);
+        self.completion = None;
+    }
+
+    /// The stream selector of the query, nothing if it doesn't parse
+    fn stream_selector(&self) -> Vec<String> {
+        let text = &self.query_textarea.lines()[0];
+        logql::parse(text)
+            .ok()
+            .and_then(|expr| {
+                expr.selector()
+                    .map(|selector| text[selector.span.clone()].to_string())
+            })
+            .into_iter()
+            .collect()
+    }
+
+    /// Updates the completion popup for the text at the cursor.
+    /// If `explicit` is false the popup only opens for obvious contexts.
+    fn update_completion(&mut self, app: &App, explicit: bool) {
+        let (_, cursor) = self.query_textarea.cursor();
+        let Some(context) = Context::at(&self.query_textarea.lines()[0], cursor) else {
+            self.completion = None;
+            return;
+        };
+
+        let items = match &context.kind {
+            Kind::LabelName => {
+                let labels = LabelCache::labels(&app.label_cache, &app.loki, app.range());
+                context.matching(labels.iter().map(String::as_str))
+            }
+            Kind::LabelValue { label, .. } => {
+                let values = LabelCache::values(&app.label_cache, &app.loki, label, app.range());
+                context.matching(values.iter().map(String::as_str))
+            }
+            Kind::Pipeline => context.matching(PIPELINE_KEYWORDS.iter().copied()),
+            Kind::Function => context.matching(function_keywords()),
+        };
+
+        let explicit = explicit || self.completion.as_ref().is_some_and(|c| c.explicit);
+        // Without a word to complete everything matches, e.g. all functions after a `)`
+        let open = explicit
+            || (!context.prefix.is_empty()
+                && (self.completion.is_some()
+                    || matches!(context.kind, Kind::LabelName | Kind::LabelValue { .. })
+                    || context.prefix.len() >= 2));
+        self.completion = match open {
+            true => {
+                let selected = self.completion.as_ref().map_or(0, |c| c.selected);
+                Some(Completion {
+                    selected: selected.min(items.len().saturating_sub(1)),
+                    context,
+                    items,
+                    explicit,
+                })
+            }
+            false => None,
+        };
+    }
+
+    /// Replaces the word at the cursor with the selected completion
+    fn accept_completion(&mut self) {
+        let Some(completion) = self.completion.take() else {
+            return;
+        };
+        let Some(item) = completion.items.get(completion.selected) else {
+            return;
+        };
+        for _ in 0..completion.context.prefix.chars().count() {
+            self.query_textarea.delete_char();
+        }
+        self.query_textarea
+            .insert_str(completion.context.replacement(item));
+    }
+
+    fn completion_popup(&self, frame: &mut Frame, query_rect: Rect) {
+        let Some(completion) = &self.completion else {
+            return;
+        };
+        if completion.items.is_empty() {
+            return;
+        }
+
+        let width = completion
+            .items
+            .iter()
+            .map(|item| item.chars().count() as u16)
+            .max()
+            .unwrap_or(0)
+            .max(20)
+            + 2;
+        let height = completion.items.len().min(8) as u16 + 2;
+        let x =
+            query_rect.x + 1 + (completion.context.start as u16).saturating_sub(self.query_scroll);
+        let area = Rect::new(
+            x.min(frame.size().width.saturating_sub(width)),
+            query_rect.y + query_rect.height - 1,
+            width.min(frame.size().width),
+            height.min(frame.size().height.saturating_sub(query_rect.bottom())),
+        );
+
+        let items: Vec<ListItem> = completion
+            .items
+            .iter()
+            .map(|item| ListItem::new(item.as_str()))
+            .collect();
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        let mut state = ListState::default().with_selected(Some(completion.selected));
+        frame.render_widget(Clear, area);
+        frame.render_stateful_widget(list, area, &mut state);
+    }
+
+    /// Writes the query results (or the error) into the store
+    fn show_results(store: &mut Store, result: Result<LokiResult, impl Display>) {
+        store.status = None;
+        match result {
+            Ok(result) => {
+                store.results = Query::format_results(&result);
+            }
+            Err(error) => {
+                let error = error.to_string();
+                store.results = vec!["No results".to_string()];
+                for line in error.lines() {
+                    store.results.push(line.to_string());
+                }
+            }
+        }
+        store.results_changed = true;
+    }
+
+    /// Writes the results of a paged query into the store, with the line count as status
+    fn show_paged_results(store: &mut Store, result: Result<PagedResult, impl Display>) {
+        match result {
+            Ok(paged) => {
+                let is_streams = matches!(paged.result, LokiResult::Streams(_));
+                Query::show_results(store, Ok::<_, String>(paged.result));
+                if is_streams {
+                    store.status = Some(match paged.truncated {
+                        true => format!("{} lines, truncated at limit", paged.lines),
+                        false => format!("{} lines", paged.lines),
+                    });
+                }
+            }
+            Err(error) => Query::show_results(store, Err(error)),
+        }
+    }
+
+    /// Shows the query with the invalid part underlined
+    fn show_parse_error(store: &mut Store, query: &str, error: &ParseError) {
+        let start = error.span.start.min(query.len());
+        let end = error.span.end.clamp(start, query.len());
+        let offset = query[..start].chars().count();
+        let width = query[start..end].chars().count().max(1);
+
+        store.status = None;
+        store.results = vec![
+            "Invalid query".to_string(),
+            query.to_string(),
+            format!("{}{}", " ".repeat(offset), "^".repeat(width)),
+            error.message.clone(),
+        ];
+        store.results_changed = true;
+    }
+
+    fn format_results(result: &LokiResult) -> Vec<String> {
+        let mut string = String::new();
+        match result {
+            LokiResult::Streams(streams) => {
+                for stream in streams {
+                    string.push_str(&format!(
+                        "Labels: \n{}\n",
+                        serde_json::to_string_pretty(&stream.labels).unwrap()
+                    ));
+                    string.push_str("\nValues:\n");
+                    for value in &stream.values {
+                        string.push_str(&format!("  {}", value));
+                        // Kept apart from the stream labels, since they differ per line
+                        if !value.structured_metadata.is_empty() {
+                            let metadata = format_labels(&value.structured_metadata);
+                            string.push_str(&format!("  metadata {metadata}"));
+                        }
+                        if !value.parsed.is_empty() {
+                            let parsed = format_labels(&value.parsed);
+                            string.push_str(&format!("  parsed {parsed}"));
+                        }
+                        string.push('\n');
+                    }
+                    string.push('\n');
+                }
+            }
+            LokiResult::Matrix(series) => {
+                for series in series {
+                    string.push_str(&format!(
+                        "Metric: \n{}\n",
+                        serde_json::to_string_pretty(&series.metric).unwrap()
+                    ));
+                    string.push_str("\nSamples:\n");
+                    for sample in &series.values {
+                        string.push_str(&format!("  {}\n", sample));
+                    }
+                    string.push('\n');
+                }
+            }
+            LokiResult::Vector(samples) => {
+                for sample in samples {
+                    string.push_str(&format!(
+                        "Metric: \n{}\n",
+                        serde_json::to_string_pretty(&sample.metric).unwrap()
+                    ));
+                    string.push_str(&format!("\nSample:\n  {}\n\n", sample.value));
+                }
+            }
+            LokiResult::Scalar(sample) => {
+                string.push_str(&format!("Scalar:\n  {}\n", sample));
+            }
+        }
+        if result.is_empty() {
+            string.push_str("No results\n");
+        }
+        string.lines().map(str::to_string).collect()
     }
 
     fn results_frame(&mut self, frame: &mut Frame, rect: Rect, app: &App) {
@@ -106,16 +471,24 @@ impl Query<'_> {
             _ => ratatui::style::Color::White,
         };
 
+        let store = app.store.lock().unwrap();
+        let title = match &store.status {
+            Some(status) => format!("Results ({status})"),
+            None => String::from("Results"),
+        };
         let block = Block::default()
-            .title("Results")
+            .title(title)
             .borders(Borders::ALL)
             .border_style(Style::default().fg(color));
 
-        let store = app.store.lock().unwrap();
         if store.results_changed {
             self.results_textarea = TextArea::new(store.results.clone());
             self.results_textarea
                 .set_cursor_line_style(Style::default());
+            // Keep the newest lines in view while following
+            if self.follow.is_some() {
+                self.results_textarea.move_cursor(CursorMove::Bottom);
+            }
         }
 
         let inner_size = block.inner(rect);
@@ -149,8 +522,13 @@ impl Screen for Query<'_> {
             frame.size(),
         );
 
-        self.query_bar(frame, layout[0]);
+        self.query_bar(frame, layout[0], app);
         self.results_frame(frame, layout[1], app);
+        // Label data arrives in the background, refresh the candidates
+        if self.completion.is_some() {
+            self.update_completion(app, false);
+        }
+        self.completion_popup(frame, layout[0]);
 
         let height = frame.size().height;
         let offset = 3;
@@ -178,55 +556,98 @@ impl Screen for Query<'_> {
     }
 
     fn handle_key_event(&mut self, key: KeyEvent, app: &mut App) {
+        // An empty popup lets all keys through, like Enter to run the query
+        if let (Selection::Query(true), Some(completion)) = (&self.selection, &mut self.completion)
+        {
+            let count = completion.items.len();
+            match key.code {
+                _ if count == 0 => {}
+                crossterm::event::KeyCode::Tab | crossterm::event::KeyCode::Down => {
+                    completion.selected = (completion.selected + 1) % count;
+                    return;
+                }
+                crossterm::event::KeyCode::BackTab | crossterm::event::KeyCode::Up => {
+                    completion.selected = (completion.selected + count - 1) % count;
+                    return;
+                }
+                crossterm::event::KeyCode::Enter => {
+                    self.accept_completion();
+                    return;
+                }
+                crossterm::event::KeyCode::Esc => {
+                    self.completion = None;
+                    return;
+                }
+                _ => {}
+            }
+        }
+
         match self.selection {
             Selection::Query(true) => match key.code {
                 crossterm::event::KeyCode::Esc => {
                     self.selection = Selection::Query(false);
+                    self.completion = None;
+                }
+                crossterm::event::KeyCode::Tab => {
+                    app.label_cache.lock().unwrap().retry_failed();
+                    self.update_completion(app, true);
+                }
+                crossterm::event::KeyCode::Char('r')
+                    if key.modifiers.contains(KeyModifiers::CONTROL) =>
+                {
+                    self.completion = None;
+                    app.screens.push(Box::from(HistorySearch::new()));
+                }
+                crossterm::event::KeyCode::Up => {
+                    self.recall(app, true);
+                }
+                crossterm::event::KeyCode::Down => {
+                    self.recall(app, false);
                 }
                 crossterm::event::KeyCode::Enter => {
-                    self.selection = Selection::Results(false);
+                    self.completion = None;
                     let text = self.query_textarea.lines()[0].to_string();
+                    if let Err(error) = logql::parse(&text) {
+                        // Stay in the query and jump to the problem instead of asking Loki
+                        let column = text[..error.span.start.min(text.len())].chars().count();
+                        self.query_textarea
+                            .move_cursor(CursorMove::Jump(0, column as u16));
+                        Query::show_parse_error(&mut app.store.lock().unwrap(), &text, &error);
+                        return;
+                    }
+                    app.history.push(&app.loki.address, &text);
+                    self.history_position = None;
+                    self.selection = Selection::Results(false);
+                    self.follow = None;
                     let mut loki = app.loki.clone();
                     let store = app.store.clone();
+                    let mode = self.mode;
+                    let options = app.options.clone();
+                    let (start, end) = app.range();
+                    let paging = self.paging;
+                    let max_lines = app.config.max_lines;
                     thread::spawn(move || {
-                        let result = loki.query_range(&text, None, None, None);
+                        if mode == Mode::Range && paging {
+                            let result =
+                                loki.query_range_paged(&text, &options, start, end, max_lines);
+                            info!("{:?}", result);
+                            let mut store = store.lock().unwrap();
+                            Query::show_paged_results(&mut store, result);
+                            return;
+                        }
+                        let result = match mode {
+                            Mode::Range => loki.query_range(&text, &options, start, end),
+                            Mode::Instant => loki.query(&text, options.limit, end),
+                        };
                         info!("{:?}", result);
                         let mut store = store.lock().unwrap();
-                        if let Ok(result) = result {
-                            let results_text: Vec<String> = result
-                                .iter()
-                                .map(|result| {
-                                    let mut string = String::new();
-                                    string.push_str(&format!(
-                                        "Labels: \n{}\n",
-                                        serde_json::to_string_pretty(&result.labels).unwrap()
-                                    ));
-                                    string.push_str("\nValues:\n");
-                                    for value in &result.values {
-                                        string.push_str(&format!("  {}\n", value));
-                                    }
-                                    string.push('\n');
-                                    string
-                                })
-                                .collect();
-                            store.results = Vec::new();
-                            for result in results_text {
-                                for line in result.lines() {
-                                    store.results.push(line.to_string());
-                                }
-                            }
-                        } else {
-                            let error = result.unwrap_err().to_string();
-                            store.results = vec!["No results".to_string()];
-                            for line in error.lines() {
-                                store.results.push(line.to_string());
-                            }
-                        }
-                        store.results_changed = true;
+                        Query::show_results(&mut store, result);
                     });
                 }
                 _ => {
+                    self.history_position = None;
                     self.query_textarea.input(key);
+                    self.update_completion(app, false);
                 }
             },
             Selection::Results(true) => match key.code {
@@ -245,15 +666,69 @@ impl Screen for Query<'_> {
                     self.selection = Selection::Results(false);
                 }
                 crossterm::event::KeyCode::Char('s') => {
-                    app.screens
-                        .push(Box::from(Settings::new(app.config.loki_url.clone())));
+                    app.screens.push(Box::from(Settings::new(&app.config)));
                 }
                 crossterm::event::KeyCode::Char('q') | crossterm::event::KeyCode::Esc => {
                     self.should_close = true;
                 }
                 crossterm::event::KeyCode::Char('d') => {
+                    if app.loki.read_only {
+                        app.screens.push(Box::from(Alert::new(
+                            "Read-only mode",
+                            "Deleting is disabled, Loki is opened read-only.\nRead-only is set in the config, the profile\nor with --read-only.",
+                        )));
+                    } else {
+                        app.screens
+                            .push(Box::from(Remove::new(self.query_textarea.lines(), app)));
+                    }
+                }
+                crossterm::event::KeyCode::Char('f') => {
+                    if self.follow.take().is_none() {
+                        let text = self.query_textarea.lines()[0].to_string();
+                        app.history.push(&app.loki.address, &text);
+                        self.follow = Some(Follow::start(
+                            app.loki.clone(),
+                            text,
+                            app.config.tail_delay_for,
+                            app.store.clone(),
+                        ));
+                    }
+                }
+                crossterm::event::KeyCode::Char('p') => {
+                    if let Some(follow) = &self.follow {
+                        follow.toggle_pause();
+                    }
+                }
+                crossterm::event::KeyCode::Char('e') => {
                     app.screens
-                        .push(Box::from(Remove::new(self.query_textarea.lines())));
+                        .push(Box::from(Series::new(&self.stream_selector())));
+                }
+                crossterm::event::KeyCode::Char('l') => {
+                    app.screens.push(Box::from(LabelBrowser::new(app)));
+                }
+                crossterm::event::KeyCode::Char('v') => {
+                    app.screens.push(Box::from(SavedQueriesPicker::new(app)));
+                }
+                crossterm::event::KeyCode::Char('t') => {
+                    app.screens.push(Box::from(TimeRangePicker::new(app)));
+                }
+                crossterm::event::KeyCode::Char('o') => {
+                    app.screens.push(Box::from(Options::new(&app.options)));
+                }
+                crossterm::event::KeyCode::Char('r') => {
+                    app.screens.push(Box::from(Profiles::new(app)));
+                }
+                crossterm::event::KeyCode::Char('a') => {
+                    app.screens.push(Box::from(Audit::new(app)));
+                }
+                crossterm::event::KeyCode::Char('g') => {
+                    self.paging = !self.paging;
+                }
+                crossterm::event::KeyCode::Char('m') => {
+                    self.mode = match self.mode {
+                        Mode::Range => Mode::Instant,
+                        Mode::Instant => Mode::Range,
+                    };
                 }
                 _ => {
                     if key.code == crossterm::event::KeyCode::Enter {
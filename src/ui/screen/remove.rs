@@ -1,10 +1,33 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+use chrono::{DateTime, Local};
+
 use ratatui::layout::{Alignment, Layout, Rect};
 use ratatui::prelude::Style;
+use ratatui::style::Color;
 use ratatui::widgets::{Block, BorderType, Borders, Clear, Paragraph};
 use ratatui::Frame;
 use tui_textarea::TextArea;
 
+use crate::{
+    loki::{
+        error::Error,
+        logql::{self, ast::Expr, format_duration},
+        result::LokiResult,
+        time::TimeRange,
+        Loki, QueryOptions,
+    },
+    ui::{follow::format_labels, highlight, App},
+};
+
 use super::alert::Alert;
+use super::deletes::Deletes;
 use super::Screen;
 
 enum Buttons {
@@ -14,13 +37,37 @@ enum Buttons {
 
 enum Selection {
     Query(bool),
+    From(bool),
+    To(bool),
     Buttons(Buttons),
 }
 
+/// The number of log lines shown as a sample of what would be deleted
+const SAMPLE_LINES: i64 = 20;
+
+/// Absolute times in the confirmation, exact to the second and with the offset
+const RANGE_FORMAT: &str = "%Y-%m-%d %H:%M:%S %:z";
+
+/// What the delete would remove, loaded in the background
+enum Preview {
+    Pending,
+    Done(Vec<String>),
+    Failed(String),
+}
+
 pub struct Remove<'a> {
     should_close: bool,
+    /// Set once the delete request was sent, the results are shown in the query screen
+    done: Arc<AtomicBool>,
     query_textarea: TextArea<'a>,
+    /// The range to delete, starting out as the range of the query screen
+    from_textarea: TextArea<'a>,
+    to_textarea: TextArea<'a>,
     selection: Selection,
+    /// The preview, with the number of the refresh it belongs to
+    preview: Arc<Mutex<(usize, Preview)>>,
+    /// Counts the refreshes, so a slow earlier preview can't replace a newer one
+    generation: usize,
 }
 
 impl Remove<'_> {
@@ -37,7 +84,61 @@ impl Remove<'_> {
             .border_style(Style::default().fg(color));
         let inner_area = block.inner(rect);
         frame.render_widget(block, rect);
-        frame.render_widget(self.query_textarea.widget(), inner_area);
+        let (paragraph, _) = highlight::query_paragraph(
+            &self.query_textarea,
+            "Enter a valid query",
+            inner_area.width,
+        );
+        frame.render_widget(paragraph, inner_area);
+    }
+
+    fn range_bar(&self, frame: &mut Frame, rect: Rect) {
+        let layout = Layout::default()
+            .direction(ratatui::layout::Direction::Horizontal)
+            .constraints(
+                [
+                    ratatui::layout::Constraint::Percentage(50),
+                    ratatui::layout::Constraint::Percentage(50),
+                ]
+                .as_ref(),
+            )
+            .split(rect);
+
+        let color = |editing: Option<bool>| match editing {
+            Some(true) => Color::Yellow,
+            Some(false) => Color::Blue,
+            None => Color::White,
+        };
+        let (from, to) = match self.selection {
+            Selection::From(editing) => (Some(editing), None),
+            Selection::To(editing) => (None, Some(editing)),
+            _ => (None, None),
+        };
+        Remove::field(frame, layout[0], "From", &self.from_textarea, color(from));
+        Remove::field(frame, layout[1], "To", &self.to_textarea, color(to));
+    }
+
+    fn field(frame: &mut Frame, rect: Rect, title: &str, textarea: &TextArea, color: Color) {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(title.to_string())
+            .border_style(Style::default().fg(color));
+        frame.render_widget(textarea.widget(), block.inner(rect));
+        frame.render_widget(block, rect);
+    }
+
+    /// The resolved range, or why it can't be resolved, in one line
+    fn resolved_range(&self, frame: &mut Frame, rect: Rect) {
+        let paragraph = match self.entered_range().resolve() {
+            Ok((start, end)) => Paragraph::new(format!(
+                "Deletes from {} to {}",
+                start.format(RANGE_FORMAT),
+                end.format(RANGE_FORMAT)
+            ))
+            .style(Style::default().fg(Color::Gray)),
+            Err(e) => Paragraph::new(e.to_string()).style(Style::default().fg(Color::Red)),
+        };
+        frame.render_widget(paragraph, rect);
     }
 
     fn bottom_buttons(&self, frame: &mut Frame, rect: Rect) {
@@ -97,15 +198,153 @@ impl Remove<'_> {
         );
     }
 
-    pub fn new(query: &[String]) -> Self {
+    pub fn new(query: &[String], app: &App) -> Self {
         let mut query_textarea = TextArea::new(query.to_owned());
         query_textarea.set_cursor_line_style(Style::default());
-        query_textarea.set_placeholder_text("Enter a valid query");
-        Remove {
+        let mut from_textarea = TextArea::new(vec![app.time_range.from.clone()]);
+        from_textarea.set_cursor_line_style(Style::default());
+        let mut to_textarea = TextArea::new(vec![app.time_range.to.clone()]);
+        to_textarea.set_cursor_line_style(Style::default());
+        let mut remove = Remove {
             should_close: false,
+            done: Arc::new(AtomicBool::new(false)),
             query_textarea,
+            from_textarea,
+            to_textarea,
             selection: Selection::Buttons(Buttons::Right),
+            preview: Arc::new(Mutex::new((0, Preview::Pending))),
+            generation: 0,
+        };
+        remove.refresh_preview(app);
+        remove
+    }
+
+    fn entered_range(&self) -> TimeRange {
+        TimeRange::new(&self.from_textarea.lines()[0], &self.to_textarea.lines()[0])
+    }
+
+    /// Counts and samples the lines the query matches in the background
+    fn refresh_preview(&mut self, app: &App) {
+        self.generation += 1;
+        let generation = self.generation;
+        let preview = self.preview.clone();
+        let (start, end) = match self.entered_range().resolve() {
+            Ok(range) => range,
+            Err(error) => {
+                *preview.lock().unwrap() = (generation, Preview::Failed(error.to_string()));
+                return;
+            }
+        };
+        *preview.lock().unwrap() = (generation, Preview::Pending);
+        let query = self.query_textarea.lines()[0].to_string();
+        let loki = app.loki.clone();
+        thread::spawn(move || {
+            let result = Remove::load_preview(loki, &query, start, end);
+            let mut preview = preview.lock().unwrap();
+            // Dropped if the query or range changed in the meantime
+            if preview.0 == generation {
+                preview.1 = match result {
+                    Ok(lines) => Preview::Done(lines),
+                    Err(error) => Preview::Failed(error),
+                };
+            }
+        });
+    }
+
+    /// Only log queries select lines that can be deleted
+    fn check_query(query: &str) -> Result<(), String> {
+        match logql::parse(query) {
+            Ok(Expr::Log(_)) => Ok(()),
+            Ok(Expr::Metric(_)) => Err(String::from(
+                "Only log queries can be deleted, metric queries can't",
+            )),
+            Err(error) => Err(format!("Invalid query: {error}")),
+        }
+    }
+
+    fn load_preview(
+        mut loki: Loki,
+        query: &str,
+        start: DateTime<Local>,
+        end: DateTime<Local>,
+    ) -> Result<Vec<String>, String> {
+        Remove::check_query(query)?;
+        let range = (end - start)
+            .to_std()
+            .map_err(|_| String::from("The start of the range is after its end"))?;
+
+        // Counted at the end of the range, over all of it
+        let count_query = format!("count_over_time({query} [{}])", format_duration(range));
+        let counts = loki
+            .query(&count_query, None, Some(end))
+            .map_err(|e| e.to_string())?;
+        let options = QueryOptions {
+            limit: Some(SAMPLE_LINES),
+            ..QueryOptions::default()
+        };
+        let sample = loki
+            .query_range(query, &options, Some(start), Some(end))
+            .map_err(|e| e.to_string())?;
+        Ok(Remove::format_preview(&counts, &sample))
+    }
+
+    fn format_preview(counts: &LokiResult, sample: &LokiResult) -> Vec<String> {
+        let mut counts: Vec<(String, f64)> = match counts {
+            LokiResult::Vector(samples) => samples
+                .iter()
+                .map(|sample| (format_labels(&sample.metric), sample.value.value))
+                .collect(),
+            _ => Vec::new(),
+        };
+        counts.sort_by(|(a_labels, a), (b_labels, b)| {
+            b.total_cmp(a).then_with(|| a_labels.cmp(b_labels))
+        });
+        let total: f64 = counts.iter().map(|(_, count)| count).sum();
+
+        let mut lines = vec![format!(
+            "{total} lines in {} streams would be deleted",
+            counts.len()
+        )];
+        lines.push(String::new());
+        for (labels, count) in &counts {
+            lines.push(format!("{count:>10}  {labels}"));
         }
+
+        if let LokiResult::Streams(streams) = sample {
+            let mut values: Vec<_> = streams
+                .iter()
+                .flat_map(|stream| {
+                    let labels = format_labels(&stream.labels);
+                    stream
+                        .values
+                        .iter()
+                        .map(move |value| (value.timestamp, labels.clone(), &value.log_line))
+                })
+                .collect();
+            values.sort_by_key(|(timestamp, _, _)| std::cmp::Reverse(*timestamp));
+            if !values.is_empty() {
+                lines.push(String::new());
+                lines.push(String::from("Newest matching lines:"));
+            }
+            for (timestamp, labels, line) in values.iter().take(SAMPLE_LINES as usize) {
+                lines.push(format!("  [{timestamp}] {labels} {line}"));
+            }
+        }
+        lines
+    }
+
+    fn preview_frame(&self, frame: &mut Frame, rect: Rect) {
+        let lines = match &self.preview.lock().unwrap().1 {
+            Preview::Pending => vec![String::from("Counting matching lines...")],
+            Preview::Done(lines) => lines.clone(),
+            Preview::Failed(error) => {
+                let mut lines = vec![String::from("Unable to preview the delete")];
+                lines.extend(error.lines().map(str::to_string));
+                lines
+            }
+        };
+        let block = Block::default().borders(Borders::ALL).title("Preview");
+        frame.render_widget(Paragraph::new(lines.join("\n")).block(block), rect);
     }
 }
 
@@ -114,8 +353,8 @@ impl Screen for Remove<'_> {
         self.should_close
     }
 
-    fn render(&mut self, frame: &mut ratatui::prelude::Frame, app: &crate::ui::App) {
-        if app.store.lock().unwrap().results_changed {
+    fn render(&mut self, frame: &mut ratatui::prelude::Frame, _app: &crate::ui::App) {
+        if self.done.load(Ordering::Relaxed) {
             self.should_close = true;
             return;
         }
@@ -126,7 +365,10 @@ impl Screen for Remove<'_> {
             .constraints(
                 [
                     ratatui::layout::Constraint::Length(3),
+                    ratatui::layout::Constraint::Length(3),
+                    ratatui::layout::Constraint::Length(1),
                     ratatui::layout::Constraint::Percentage(100),
+                    ratatui::layout::Constraint::Length(1),
                     ratatui::layout::Constraint::Length(3),
                 ]
                 .as_ref(),
@@ -140,75 +382,136 @@ impl Screen for Remove<'_> {
         );
 
         self.query_bar(frame, layout[0]);
-        self.bottom_buttons(frame, layout[2]);
-        //self.results_frame(frame, layout[1], app);
+        self.range_bar(frame, layout[1]);
+        self.resolved_range(frame, layout[2]);
+        self.preview_frame(frame, layout[3]);
+        frame.render_widget(
+            Paragraph::new("p: refresh preview ─ l: list delete requests")
+                .style(Style::default().fg(Color::Gray)),
+            layout[4],
+        );
+        self.bottom_buttons(frame, layout[5]);
     }
 
     fn handle_key_event(&mut self, key: crossterm::event::KeyEvent, app: &mut crate::ui::App) {
-        match self.selection {
-            Selection::Query(true) => match key.code {
+        let textarea = match self.selection {
+            Selection::Query(true) => Some(&mut self.query_textarea),
+            Selection::From(true) => Some(&mut self.from_textarea),
+            Selection::To(true) => Some(&mut self.to_textarea),
+            _ => None,
+        };
+        if let Some(textarea) = textarea {
+            match key.code {
                 crossterm::event::KeyCode::Esc | crossterm::event::KeyCode::Enter => {
-                    self.selection = Selection::Query(false);
+                    self.selection = match self.selection {
+                        Selection::From(_) => Selection::From(false),
+                        Selection::To(_) => Selection::To(false),
+                        _ => Selection::Query(false),
+                    };
+                    self.refresh_preview(app);
                 }
                 _ => {
-                    self.query_textarea.input(key);
+                    textarea.input(key);
                 }
+            }
+            return;
+        }
+
+        match key.code {
+            crossterm::event::KeyCode::Char('q') | crossterm::event::KeyCode::Esc => {
+                self.should_close = true;
+            }
+            crossterm::event::KeyCode::Char('p') => {
+                self.refresh_preview(app);
+            }
+            crossterm::event::KeyCode::Char('l') => {
+                app.screens.push(Box::from(Deletes::new(app)));
+            }
+            crossterm::event::KeyCode::Down => {
+                self.selection = match self.selection {
+                    Selection::Query(_) => Selection::From(false),
+                    _ => Selection::Buttons(Buttons::Right),
+                };
+            }
+            crossterm::event::KeyCode::Up => {
+                self.selection = match self.selection {
+                    Selection::Buttons(_) => Selection::From(false),
+                    _ => Selection::Query(false),
+                };
+            }
+            crossterm::event::KeyCode::Left => match self.selection {
+                Selection::Buttons(_) => self.selection = Selection::Buttons(Buttons::Left),
+                Selection::To(_) => self.selection = Selection::From(false),
+                _ => {}
             },
-            _ => match key.code {
-                crossterm::event::KeyCode::Char('q') | crossterm::event::KeyCode::Esc => {
-                    self.should_close = true;
-                }
-                crossterm::event::KeyCode::Down => {
-                    self.selection = Selection::Buttons(Buttons::Right);
+            crossterm::event::KeyCode::Right => match self.selection {
+                Selection::Buttons(_) => self.selection = Selection::Buttons(Buttons::Right),
+                Selection::From(_) => self.selection = Selection::To(false),
+                _ => {}
+            },
+            crossterm::event::KeyCode::Enter => match self.selection {
+                Selection::Query(false) => {
+                    self.selection = Selection::Query(true);
                 }
-                crossterm::event::KeyCode::Up => {
-                    self.selection = Selection::Query(false);
+                Selection::From(false) => {
+                    self.selection = Selection::From(true);
                 }
-                crossterm::event::KeyCode::Left => {
-                    if let Selection::Buttons(_) = self.selection {
-                        self.selection = Selection::Buttons(Buttons::Left);
-                    }
+                Selection::To(false) => {
+                    self.selection = Selection::To(true);
                 }
-                crossterm::event::KeyCode::Right => {
-                    if let Selection::Buttons(_) = self.selection {
-                        self.selection = Selection::Buttons(Buttons::Right);
-                    }
+                Selection::Buttons(Buttons::Right) => {
+                    self.should_close = true;
                 }
-                crossterm::event::KeyCode::Enter => match self.selection {
-                    Selection::Query(false) => {
-                        self.selection = Selection::Query(true);
+                Selection::Buttons(Buttons::Left) => {
+                    if app.loki.read_only {
+                        app.screens.push(Box::from(Alert::new(
+                            "Read-only mode",
+                            Error::ReadOnly("deleting").to_string(),
+                        )));
+                        return;
                     }
-                    Selection::Buttons(Buttons::Right) => {
-                        self.should_close = true;
+                    // Resolved once, so the confirmed range is the one that gets deleted
+                    let Ok((start, end)) = self.entered_range().resolve() else {
+                        self.selection = Selection::From(false);
+                        return;
+                    };
+                    let query = self.query_textarea.lines()[0].to_string();
+                    if let Err(error) = Remove::check_query(&query) {
+                        app.screens
+                            .push(Box::from(Alert::new("Unable to delete", error)));
+                        return;
                     }
-                    Selection::Buttons(Buttons::Left) => {
-                        let loki = app.loki.clone();
-                        let query = self.query_textarea.lines()[0].to_string();
-                        let store = app.store.clone();
-                        app.screens.push(Box::from(Alert::with_action(
-                            "Remove data for query?",
-                            "This will create a delete request for all Data\nfound with this Query. Once the delete request\nis executed, you cant undo this!",
-                            move || {
-                                let mut loki = loki.clone();
-                                let result = loki.delete(&query, None, None);
-                                let mut store = store.lock().unwrap();
-                                if result.is_err() {
-                                    let error = result.unwrap_err().to_string();
-                                    store.results = Vec::new();
-                                    for line in error.lines() {
-                                        store.results.push(line.to_string());
-                                    }
-                                } else {
-                                    store.results = vec!["Deletion request sent!".to_string()];
+                    let loki = app.loki.clone();
+                    let store = app.store.clone();
+                    let done = self.done.clone();
+                    app.screens.push(Box::from(Alert::with_action(
+                        "Remove data for query?",
+                        format!(
+                            "This will create a delete request for all Data\nfound with this Query from\n  {}\nto\n  {}\nOnce the delete request is executed,\nyou cant undo this!",
+                            start.format(RANGE_FORMAT),
+                            end.format(RANGE_FORMAT)
+                        ),
+                        move || {
+                            let mut loki = loki.clone();
+                            let result = loki.delete(&query, Some(start), Some(end));
+                            let mut store = store.lock().unwrap();
+                            if let Err(error) = result {
+                                let error = error.to_string();
+                                store.results = Vec::new();
+                                for line in error.lines() {
+                                    store.results.push(line.to_string());
                                 }
-                                store.results_changed = true;
-                            },
-                        )));
-                    }
-                    _ => (),
-                },
-                _ => {}
+                            } else {
+                                store.results = vec!["Deletion request sent!".to_string()];
+                            }
+                            store.results_changed = true;
+                            done.store(true, Ordering::Relaxed);
+                        },
+                    )));
+                }
+                _ => (),
             },
+            _ => {}
         }
     }
 }
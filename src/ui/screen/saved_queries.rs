@@ -0,0 +1,151 @@
+use std::{path::Path, sync::Arc};
+
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::Layout,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::ui::{
+    saved_queries::{SavedQueries, SavedQuery},
+    App, QueryEdit,
+};
+
+use super::Screen;
+
+///
+/// Lists the queries of the saved queries file.
+/// Loading one replaces the query and sets its range and limit.
+pub struct SavedQueriesPicker {
+    queries: Result<Vec<SavedQuery>, String>,
+    state: ListState,
+    should_close: bool,
+}
+
+impl SavedQueriesPicker {
+    pub fn new(app: &App) -> Self {
+        let queries = match &app.config.saved_queries_path {
+            Some(path) => SavedQueries::load(Path::new(path)).map(|saved| saved.queries),
+            None => Err(String::from(
+                "No saved queries file configured, set one in the settings",
+            )),
+        };
+        Self {
+            queries,
+            state: ListState::default().with_selected(Some(0)),
+            should_close: false,
+        }
+    }
+
+    fn selected(&self) -> Option<&SavedQuery> {
+        let queries = self.queries.as_ref().ok()?;
+        queries.get(self.state.selected()?)
+    }
+}
+
+impl Screen for SavedQueriesPicker {
+    fn should_close(&self) -> bool {
+        self.should_close
+    }
+
+    fn render(&mut self, frame: &mut Frame, _app: &App) {
+        let layout = Layout::default()
+            .direction(ratatui::layout::Direction::Vertical)
+            .margin(1)
+            .constraints(
+                [
+                    ratatui::layout::Constraint::Percentage(100),
+                    ratatui::layout::Constraint::Length(6),
+                    ratatui::layout::Constraint::Length(1),
+                ]
+                .as_ref(),
+            )
+            .split(frame.size());
+
+        frame.render_widget(Clear, frame.size());
+        frame.render_widget(
+            Block::default()
+                .title("Saved queries")
+                .borders(Borders::ALL),
+            frame.size(),
+        );
+
+        let items: Vec<ListItem> = match &self.queries {
+            Err(error) => vec![ListItem::new(error.clone())],
+            Ok(queries) if queries.is_empty() => vec![ListItem::new("No saved queries")],
+            Ok(queries) => queries
+                .iter()
+                .map(|query| {
+                    ListItem::new(Line::from(vec![
+                        Span::styled(query.name.clone(), Style::default().fg(Color::Blue)),
+                        Span::raw(" "),
+                        Span::styled(query.description.clone(), Style::default().fg(Color::Gray)),
+                    ]))
+                })
+                .collect(),
+        };
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title("Queries"))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, layout[0], &mut self.state);
+
+        let details = match self.selected() {
+            Some(query) => vec![
+                Line::from(query.query.clone()),
+                Line::from(format!(
+                    "range: {} ─ limit: {}",
+                    query.range.as_deref().unwrap_or("default"),
+                    query
+                        .limit
+                        .map_or(String::from("default"), |limit| limit.to_string())
+                )),
+            ],
+            None => Vec::new(),
+        };
+        frame.render_widget(
+            Paragraph::new(details)
+                .wrap(Wrap { trim: false })
+                .block(Block::default().borders(Borders::ALL).title("Query")),
+            layout[1],
+        );
+
+        frame.render_widget(
+            Paragraph::new("enter: load ─ q: quit").style(Style::default().fg(Color::Gray)),
+            layout[2],
+        );
+    }
+
+    fn handle_key_event(&mut self, key: crossterm::event::KeyEvent, app: &mut App) {
+        match key.code {
+            KeyCode::Up => {
+                let selected = self.state.selected().unwrap_or(0);
+                self.state.select(Some(selected.saturating_sub(1)));
+            }
+            KeyCode::Down => {
+                let selected = self.state.selected().unwrap_or(0);
+                let count = self.queries.as_ref().map_or(0, Vec::len);
+                self.state
+                    .select(Some((selected + 1).min(count.saturating_sub(1))));
+            }
+            KeyCode::Enter => {
+                if let Some(query) = self.selected().cloned() {
+                    app.options.limit = query.limit;
+                    if let Some(time_range) = query.time_range() {
+                        app.time_range = time_range;
+                        // The known labels depend on the range
+                        app.label_cache = Arc::default();
+                    }
+                    app.store.lock().unwrap().query_edit = Some(QueryEdit::Replace(query.query));
+                }
+                self.should_close = true;
+            }
+            KeyCode::Char('q') | KeyCode::Esc => {
+                self.should_close = true;
+            }
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,259 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Layout, Rect},
+    style::Style,
+    widgets::{Block, Borders, Clear},
+    Frame,
+};
+use tui_textarea::TextArea;
+
+use chrono::{DateTime, Local};
+
+use crate::{loki::Loki, ui::App};
+
+use super::Screen;
+
+/// How many values are listed below every label
+const TOP_VALUES: usize = 10;
+
+enum Selection {
+    Selector(bool),
+    Results(bool),
+}
+
+///
+/// Lists the streams matching a selector and how many series every label value contributes.
+/// This helps finding the labels that push a query over the stream limit.
+pub struct Series<'a> {
+    selector_textarea: TextArea<'a>,
+    results_textarea: TextArea<'a>,
+    results: Arc<Mutex<Option<Vec<String>>>>,
+    selection: Selection,
+    should_close: bool,
+}
+
+impl Series<'_> {
+    pub fn new(selector: &[String]) -> Self {
+        let mut selector_textarea = TextArea::new(selector.to_owned());
+        selector_textarea.set_cursor_line_style(Style::default());
+        selector_textarea.set_placeholder_text("Enter a stream selector, e.g. {job=\"a\"}");
+        let mut results_textarea = TextArea::new(vec![String::from(
+            "Press enter to count the series matching the selector above",
+        )]);
+        results_textarea.set_cursor_line_style(Style::default());
+        Self {
+            selector_textarea,
+            results_textarea,
+            results: Arc::new(Mutex::new(None)),
+            selection: Selection::Selector(false),
+            should_close: false,
+        }
+    }
+
+    fn selector_bar(&self, frame: &mut Frame, rect: Rect) {
+        let color = match self.selection {
+            Selection::Selector(true) => ratatui::style::Color::Yellow,
+            Selection::Selector(false) => ratatui::style::Color::Blue,
+            _ => ratatui::style::Color::White,
+        };
+
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title("Selector")
+            .border_style(Style::default().fg(color));
+        let inner_area = block.inner(rect);
+        frame.render_widget(block, rect);
+        frame.render_widget(self.selector_textarea.widget(), inner_area);
+    }
+
+    fn results_frame(&mut self, frame: &mut Frame, rect: Rect) {
+        let color = match self.selection {
+            Selection::Results(true) => ratatui::style::Color::Yellow,
+            Selection::Results(false) => ratatui::style::Color::Blue,
+            _ => ratatui::style::Color::White,
+        };
+
+        let block = Block::default()
+            .title("Cardinality")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(color));
+
+        if let Some(results) = self.results.lock().unwrap().take() {
+            self.results_textarea = TextArea::new(results);
+            self.results_textarea
+                .set_cursor_line_style(Style::default());
+        }
+
+        let inner_size = block.inner(rect);
+        frame.render_widget(block, rect);
+        frame.render_widget(self.results_textarea.widget(), inner_size);
+    }
+
+    /// Loads the cardinality in the background
+    fn run(&self, app: &App) {
+        let mut loki = app.loki.clone();
+        let (start, end) = app.range();
+        let selector = self.selector_textarea.lines()[0].trim().to_string();
+        let results = self.results.clone();
+        *results.lock().unwrap() = Some(vec![String::from("Loading...")]);
+        thread::spawn(move || {
+            let lines = if selector.is_empty() {
+                Series::label_cardinality(&mut loki, start, end)
+            } else {
+                match loki.series(&[&selector], start, end) {
+                    Ok(series) => Series::series_cardinality(&selector, &series),
+                    Err(e) => e.to_string().lines().map(str::to_string).collect(),
+                }
+            };
+            *results.lock().unwrap() = Some(lines);
+        });
+    }
+
+    /// Counts the series every label and label value is part of
+    fn series_cardinality(selector: &str, series: &[HashMap<String, String>]) -> Vec<String> {
+        let mut labels: HashMap<&str, HashMap<&str, usize>> = HashMap::new();
+        for stream in series {
+            for (label, value) in stream {
+                *labels.entry(label).or_default().entry(value).or_default() += 1;
+            }
+        }
+
+        let mut labels: Vec<_> = labels.into_iter().collect();
+        labels
+            .sort_by(|(a_label, a), (b_label, b)| b.len().cmp(&a.len()).then(a_label.cmp(b_label)));
+
+        let mut lines = vec![
+            format!("{} series matching {}", series.len(), selector),
+            String::new(),
+            format!("{:<40} {:>8} {:>8}", "Label", "Values", "Series"),
+        ];
+        for (label, values) in labels {
+            let total: usize = values.values().sum();
+            lines.push(format!("{:<40} {:>8} {:>8}", label, values.len(), total));
+
+            let mut values: Vec<_> = values.into_iter().collect();
+            values.sort_by(|(a_value, a), (b_value, b)| b.cmp(a).then(a_value.cmp(b_value)));
+            for (value, count) in values.iter().take(TOP_VALUES) {
+                lines.push(format!("  {:<38} {:>8} {:>8}", value, "", count));
+            }
+            if values.len() > TOP_VALUES {
+                lines.push(format!("  ... {} more", values.len() - TOP_VALUES));
+            }
+        }
+        lines
+    }
+
+    /// Without a selector we can only count the values of every label
+    fn label_cardinality(
+        loki: &mut Loki,
+        start: Option<DateTime<Local>>,
+        end: Option<DateTime<Local>>,
+    ) -> Vec<String> {
+        let labels = match loki.labels(start, end) {
+            Ok(labels) => labels,
+            Err(error) => {
+                let mut lines = vec![String::from("Unable to load labels")];
+                lines.extend(error.to_string().lines().map(str::to_string));
+                return lines;
+            }
+        };
+
+        let mut counts: Vec<_> = labels
+            .into_iter()
+            .map(|label| {
+                let values = loki.label_values(&label, start, end, None);
+                (label, values.ok().map(|values| values.len()))
+            })
+            .collect();
+        counts.sort_by(|(a_label, a), (b_label, b)| b.cmp(a).then(a_label.cmp(b_label)));
+
+        let mut lines = vec![
+            String::from("Values per label, enter a selector to count series"),
+            String::new(),
+            format!("{:<40} {:>8}", "Label", "Values"),
+        ];
+        for (label, count) in counts {
+            let count = count.map_or(String::from("?"), |count| count.to_string());
+            lines.push(format!("{:<40} {:>8}", label, count));
+        }
+        lines
+    }
+}
+
+impl Screen for Series<'_> {
+    fn should_close(&self) -> bool {
+        self.should_close
+    }
+
+    fn render(&mut self, frame: &mut Frame, _app: &App) {
+        let layout = Layout::default()
+            .direction(ratatui::layout::Direction::Vertical)
+            .margin(1)
+            .constraints(
+                [
+                    ratatui::layout::Constraint::Length(3),
+                    ratatui::layout::Constraint::Percentage(100),
+                ]
+                .as_ref(),
+            )
+            .split(frame.size());
+
+        frame.render_widget(Clear, frame.size());
+        frame.render_widget(
+            Block::default().title("Series").borders(Borders::ALL),
+            frame.size(),
+        );
+
+        self.selector_bar(frame, layout[0]);
+        self.results_frame(frame, layout[1]);
+    }
+
+    fn handle_key_event(&mut self, key: crossterm::event::KeyEvent, app: &mut App) {
+        match self.selection {
+            Selection::Selector(true) => match key.code {
+                KeyCode::Esc => {
+                    self.selection = Selection::Selector(false);
+                }
+                KeyCode::Enter => {
+                    self.selection = Selection::Results(false);
+                    self.run(app);
+                }
+                _ => {
+                    self.selector_textarea.input(key);
+                }
+            },
+            Selection::Results(true) => match key.code {
+                KeyCode::Esc => {
+                    self.selection = Selection::Results(false);
+                }
+                _ => {
+                    self.results_textarea.input(key);
+                }
+            },
+            Selection::Selector(false) | Selection::Results(false) => match key.code {
+                KeyCode::Up => {
+                    self.selection = Selection::Selector(false);
+                }
+                KeyCode::Down => {
+                    self.selection = Selection::Results(false);
+                }
+                KeyCode::Char('q') | KeyCode::Esc => {
+                    self.should_close = true;
+                }
+                KeyCode::Enter => {
+                    self.selection = match self.selection {
+                        Selection::Selector(_) => Selection::Selector(true),
+                        Selection::Results(_) => Selection::Results(true),
+                    }
+                }
+                _ => {}
+            },
+        }
+    }
+}
@@ -1,35 +1,103 @@
+use std::sync::Arc;
+
 use ratatui::{
-    layout::{Layout, Rect},
+    layout::{Constraint, Layout, Rect},
     style::Style,
     widgets::{Block, Borders, Clear},
 };
 use tui_textarea::TextArea;
 
-use crate::ui::App;
+use crate::{ui::App, LokiConfig};
 
 use super::Screen;
 
-enum Selection {
-    Url,
-    None,
+struct Field<'a> {
+    title: &'static str,
+    textarea: TextArea<'a>,
+}
+
+impl Field<'_> {
+    fn new(title: &'static str, value: Option<&String>, secret: bool) -> Self {
+        let mut textarea = TextArea::new(vec![value.cloned().unwrap_or_default()]);
+        textarea.set_cursor_line_style(Style::default());
+        if secret {
+            textarea.set_mask_char('*');
+        }
+        Self { title, textarea }
+    }
+
+    /// The entered text, or `None` if the field was left empty
+    fn value(&self) -> Option<String> {
+        let value = self.textarea.lines()[0].trim();
+        if value.is_empty() {
+            None
+        } else {
+            Some(value.to_string())
+        }
+    }
 }
 
 pub struct Settings<'a> {
-    loki_url: TextArea<'a>,
-    selection: Selection,
+    fields: Vec<Field<'a>>,
+    selected: usize,
+    editing: bool,
     should_close: bool,
 }
 
 impl Settings<'_> {
-    pub fn new(url: String) -> Self {
-        let mut loki_url = TextArea::new(vec![url]);
-        loki_url.set_cursor_line_style(Style::default());
+    pub fn new(config: &LokiConfig) -> Self {
         Self {
-            loki_url,
-            selection: Selection::None,
+            fields: vec![
+                Field::new("Loki URL", Some(&config.loki_url), false),
+                Field::new("Username", config.loki_username.as_ref(), false),
+                Field::new("Password", config.loki_password.as_ref(), true),
+                Field::new("Bearer token", config.loki_bearer_token.as_ref(), true),
+                Field::new(
+                    "Tenant (X-Scope-OrgID)",
+                    config.loki_tenant_id.as_ref(),
+                    false,
+                ),
+                Field::new(
+                    "Tail delay_for (seconds, max 5)",
+                    Some(&config.tail_delay_for.to_string()),
+                    false,
+                ),
+                Field::new(
+                    "Saved queries file (.yaml or .toml)",
+                    config.saved_queries_path.as_ref(),
+                    false,
+                ),
+                Field::new(
+                    "Max lines when paging",
+                    Some(&config.max_lines.to_string()),
+                    false,
+                ),
+            ],
+            selected: 0,
+            editing: false,
             should_close: false,
         }
     }
+
+    /// Writes the entered values back into the config and recreates the client
+    fn save(&self, app: &mut App) {
+        app.config.loki_url = self.fields[0].value().unwrap_or_default();
+        app.config.loki_username = self.fields[1].value();
+        app.config.loki_password = self.fields[2].value();
+        app.config.loki_bearer_token = self.fields[3].value();
+        app.config.loki_tenant_id = self.fields[4].value();
+        if let Some(Ok(delay_for)) = self.fields[5].value().map(|value| value.parse::<u32>()) {
+            app.config.tail_delay_for = delay_for.min(5);
+        }
+        app.config.saved_queries_path = self.fields[6].value();
+        if let Some(Ok(max_lines)) = self.fields[7].value().map(|value| value.parse::<usize>()) {
+            app.config.max_lines = max_lines.max(1);
+        }
+        app.config.update_profile();
+        app.loki = app.config.loki();
+        // The labels may differ on the new Loki
+        app.label_cache = Arc::default();
+    }
 }
 
 impl Screen for Settings<'_> {
@@ -41,59 +109,69 @@ impl Screen for Settings<'_> {
         let settings_window_block = Block::default().title("Settings").borders(Borders::ALL);
         let mut settings_window_size = Rect::default();
         settings_window_size.width = 60;
-        settings_window_size.height = 6;
-        settings_window_size.x = (frame.size().width / 2) - (settings_window_size.width / 2);
-        settings_window_size.y = (frame.size().height / 2) - (settings_window_size.height / 2);
+        settings_window_size.height = 3 * self.fields.len() as u16 + 3;
+        settings_window_size.x =
+            (frame.size().width / 2).saturating_sub(settings_window_size.width / 2);
+        settings_window_size.y =
+            (frame.size().height / 2).saturating_sub(settings_window_size.height / 2);
+        // Small terminals cut off the last fields instead of drawing outside the buffer
+        let settings_window_size = settings_window_size.intersection(frame.size());
         let inner_size = settings_window_block.inner(settings_window_size);
         frame.render_widget(Clear, inner_size);
         frame.render_widget(settings_window_block, settings_window_size);
 
+        let mut constraints = vec![Constraint::Length(1)];
+        constraints.extend(self.fields.iter().map(|_| Constraint::Length(3)));
+        constraints.push(Constraint::Percentage(100));
         let layout = Layout::default()
             .direction(ratatui::layout::Direction::Vertical)
-            .constraints([
-                ratatui::layout::Constraint::Length(1),
-                ratatui::layout::Constraint::Length(3),
-                ratatui::layout::Constraint::Percentage(100),
-            ])
+            .constraints(constraints)
             .split(inner_size);
 
-        let style = match self.selection {
-            Selection::Url => ratatui::style::Style::default().fg(ratatui::style::Color::Yellow),
-            Selection::None => ratatui::style::Style::default().fg(ratatui::style::Color::Blue),
-        };
-
-        let block = Block::default()
-            .borders(Borders::ALL)
-            .title("Loki URL")
-            .style(style);
-        frame.render_widget(self.loki_url.widget(), block.inner(layout[1]));
-        frame.render_widget(block, layout[1]);
+        for (i, field) in self.fields.iter().enumerate() {
+            let color = match (i == self.selected, self.editing) {
+                (true, true) => ratatui::style::Color::Yellow,
+                (true, false) => ratatui::style::Color::Blue,
+                _ => ratatui::style::Color::White,
+            };
+
+            let block = Block::default()
+                .borders(Borders::ALL)
+                .title(field.title)
+                .border_style(Style::default().fg(color));
+            frame.render_widget(field.textarea.widget(), block.inner(layout[i + 1]));
+            frame.render_widget(block, layout[i + 1]);
+        }
     }
 
     fn handle_key_event(&mut self, key: crossterm::event::KeyEvent, app: &mut App) {
-        match self.selection {
-            Selection::Url => match key.code {
-                crossterm::event::KeyCode::Esc => {
-                    self.selection = Selection::None;
-                }
-                crossterm::event::KeyCode::Enter => {
-                    self.selection = Selection::None;
+        if self.editing {
+            match key.code {
+                crossterm::event::KeyCode::Esc | crossterm::event::KeyCode::Enter => {
+                    self.editing = false;
                 }
                 _ => {
-                    self.loki_url.input(key);
+                    self.fields[self.selected].textarea.input(key);
                 }
-            },
-            Selection::None => match key.code {
-                crossterm::event::KeyCode::Esc | crossterm::event::KeyCode::Char('q') => {
-                    app.config.loki_url = self.loki_url.lines()[0].to_string();
-                    app.loki.address = self.loki_url.lines()[0].to_string();
-                    self.should_close = true;
-                }
-                crossterm::event::KeyCode::Enter => {
-                    self.selection = Selection::Url;
-                }
-                _ => {}
-            },
+            }
+            return;
+        }
+
+        match key.code {
+            crossterm::event::KeyCode::Esc | crossterm::event::KeyCode::Char('q') => {
+                self.save(app);
+                self.should_close = true;
+            }
+            crossterm::event::KeyCode::Up => {
+                self.selected = self.selected.saturating_sub(1);
+            }
+            crossterm::event::KeyCode::Down => {
+                self.selected = (self.selected + 1).min(self.fields.len() - 1);
+            }
+            crossterm::event::KeyCode::Enter => {
+                self.editing = true;
+            }
+            _ => {}
         }
     }
 }
@@ -0,0 +1,230 @@
+use std::sync::Arc;
+
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+use tui_textarea::TextArea;
+
+use crate::{loki::time::TimeRange, ui::App};
+
+use super::Screen;
+
+/// Ranges that can be picked without typing
+const QUICK_RANGES: &[(&str, &str, &str)] = &[
+    ("Last 5 minutes", "now-5m", "now"),
+    ("Last 15 minutes", "now-15m", "now"),
+    ("Last hour", "now-1h", "now"),
+    ("Last 6 hours", "now-6h", "now"),
+    ("Last 24 hours", "now-24h", "now"),
+    ("Last 7 days", "now-7d", "now"),
+    ("Today so far", "now/d", "now"),
+    ("Yesterday", "now-1d/d", "now-1d/d"),
+    ("This week so far", "now/w", "now"),
+];
+
+#[derive(PartialEq)]
+enum Selection {
+    From(bool),
+    To(bool),
+    Quick,
+}
+
+///
+/// Picks the time range for queries and lookups.
+/// Accepts relative times like `now-2d/d`, RFC3339 and unix timestamps.
+pub struct TimeRangePicker<'a> {
+    from_textarea: TextArea<'a>,
+    to_textarea: TextArea<'a>,
+    quick_state: ListState,
+    selection: Selection,
+    should_close: bool,
+}
+
+impl TimeRangePicker<'_> {
+    pub fn new(app: &App) -> Self {
+        let mut from_textarea = TextArea::new(vec![app.time_range.from.clone()]);
+        from_textarea.set_cursor_line_style(Style::default());
+        let mut to_textarea = TextArea::new(vec![app.time_range.to.clone()]);
+        to_textarea.set_cursor_line_style(Style::default());
+        Self {
+            from_textarea,
+            to_textarea,
+            quick_state: ListState::default(),
+            selection: Selection::From(false),
+            should_close: false,
+        }
+    }
+
+    fn entered(&self) -> TimeRange {
+        TimeRange::new(&self.from_textarea.lines()[0], &self.to_textarea.lines()[0])
+    }
+
+    /// Uses the range for everything from now on
+    fn apply(&mut self, time_range: TimeRange, app: &mut App) {
+        app.time_range = time_range;
+        // The known labels depend on the range
+        app.label_cache = Arc::default();
+        self.should_close = true;
+    }
+
+    fn field(frame: &mut Frame, rect: Rect, title: &str, textarea: &TextArea, color: Color) {
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(title.to_string())
+            .border_style(Style::default().fg(color));
+        frame.render_widget(textarea.widget(), block.inner(rect));
+        frame.render_widget(block, rect);
+    }
+
+    fn color(&self, selection: Selection) -> Color {
+        match (&self.selection, selection) {
+            (Selection::From(true), Selection::From(_))
+            | (Selection::To(true), Selection::To(_)) => Color::Yellow,
+            (Selection::From(false), Selection::From(_))
+            | (Selection::To(false), Selection::To(_))
+            | (Selection::Quick, Selection::Quick) => Color::Blue,
+            _ => Color::White,
+        }
+    }
+}
+
+impl Screen for TimeRangePicker<'_> {
+    fn should_close(&self) -> bool {
+        self.should_close
+    }
+
+    fn render(&mut self, frame: &mut Frame, _app: &App) {
+        let mut area = Rect::new(0, 0, 60, 3 * 2 + QUICK_RANGES.len() as u16 + 6);
+        area.x = (frame.size().width / 2).saturating_sub(area.width / 2);
+        area.y = (frame.size().height / 2).saturating_sub(area.height / 2);
+        let area = area.intersection(frame.size());
+
+        let block = Block::default().title("Time range").borders(Borders::ALL);
+        let inner = block.inner(area);
+        frame.render_widget(Clear, area);
+        frame.render_widget(block, area);
+
+        let layout = Layout::default()
+            .direction(ratatui::layout::Direction::Vertical)
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Length(3),
+                Constraint::Length(1),
+                Constraint::Percentage(100),
+                Constraint::Length(1),
+            ])
+            .split(inner);
+
+        let from_color = self.color(Selection::From(false));
+        let to_color = self.color(Selection::To(false));
+        TimeRangePicker::field(frame, layout[0], "From", &self.from_textarea, from_color);
+        TimeRangePicker::field(frame, layout[1], "To", &self.to_textarea, to_color);
+
+        let preview = match self.entered().resolve() {
+            Ok((from, to)) => Paragraph::new(format!(
+                "{} to {}",
+                from.format("%Y-%m-%d %H:%M:%S"),
+                to.format("%Y-%m-%d %H:%M:%S")
+            ))
+            .style(Style::default().fg(Color::Gray)),
+            Err(e) => Paragraph::new(e.to_string()).style(Style::default().fg(Color::Red)),
+        };
+        frame.render_widget(preview, layout[2]);
+
+        let items: Vec<ListItem> = QUICK_RANGES
+            .iter()
+            .map(|(name, from, to)| ListItem::new(format!("{name:<20} {from} to {to}")))
+            .collect();
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Quick ranges")
+                    .border_style(Style::default().fg(self.color(Selection::Quick))),
+            )
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, layout[3], &mut self.quick_state);
+
+        frame.render_widget(
+            Paragraph::new("enter: edit / apply ─ esc: close")
+                .style(Style::default().fg(Color::Gray)),
+            layout[4],
+        );
+    }
+
+    fn handle_key_event(&mut self, key: crossterm::event::KeyEvent, app: &mut App) {
+        let textarea = match self.selection {
+            Selection::From(true) => Some(&mut self.from_textarea),
+            Selection::To(true) => Some(&mut self.to_textarea),
+            _ => None,
+        };
+        if let Some(textarea) = textarea {
+            match key.code {
+                KeyCode::Esc => {
+                    self.selection = match self.selection {
+                        Selection::From(_) => Selection::From(false),
+                        _ => Selection::To(false),
+                    };
+                }
+                KeyCode::Enter => {
+                    // Only a range that can be resolved is taken over
+                    let time_range = self.entered();
+                    if time_range.resolve().is_ok() {
+                        self.apply(time_range, app);
+                    }
+                }
+                _ => {
+                    textarea.input(key);
+                }
+            }
+            return;
+        }
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.should_close = true;
+            }
+            KeyCode::Up => match self.selection {
+                Selection::To(_) => self.selection = Selection::From(false),
+                Selection::Quick => match self.quick_state.selected() {
+                    Some(0) | None => {
+                        self.quick_state.select(None);
+                        self.selection = Selection::To(false);
+                    }
+                    Some(i) => self.quick_state.select(Some(i - 1)),
+                },
+                Selection::From(_) => {}
+            },
+            KeyCode::Down => match self.selection {
+                Selection::From(_) => self.selection = Selection::To(false),
+                Selection::To(_) => {
+                    self.selection = Selection::Quick;
+                    self.quick_state.select(Some(0));
+                }
+                Selection::Quick => {
+                    let selected = self.quick_state.selected().unwrap_or(0);
+                    self.quick_state
+                        .select(Some((selected + 1).min(QUICK_RANGES.len() - 1)));
+                }
+            },
+            KeyCode::Enter => match self.selection {
+                Selection::From(_) => self.selection = Selection::From(true),
+                Selection::To(_) => self.selection = Selection::To(true),
+                Selection::Quick => {
+                    if let Some((_, from, to)) = self
+                        .quick_state
+                        .selected()
+                        .and_then(|i| QUICK_RANGES.get(i))
+                    {
+                        self.apply(TimeRange::new(from, to), app);
+                    }
+                }
+            },
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use chrono::{Local, TimeZone};
+use loki_ui::loki::audit::{AuditLog, AuditRecord, Operation};
+
+#[test]
+fn audit_file() {
+    let path = std::env::temp_dir().join(format!("loki_ui_audit_{}.jsonl", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let audit_log = AuditLog::new(path.clone());
+    assert!(audit_log.records().unwrap().is_empty());
+
+    let record = AuditRecord {
+        timestamp: Local.timestamp_opt(1_700_000_000, 0).unwrap(),
+        user: String::from("alice"),
+        url: String::from("http://localhost:3100"),
+        tenant: Some(String::from("team-a")),
+        operation: Operation::Delete,
+        query: String::from("{job=\"api\"} |= \"secret\""),
+        start: Some(Local.timestamp_opt(1_699_990_000, 0).unwrap()),
+        end: Some(Local.timestamp_opt(1_700_000_000, 0).unwrap()),
+        response: String::from("accepted with status 204"),
+    };
+    let push = AuditRecord {
+        operation: Operation::Push,
+        query: String::from("{job=\"fixture\"}"),
+        tenant: None,
+        response: String::from("Loki answered with status 400:\nentry too far behind"),
+        ..record.clone()
+    };
+    audit_log.append(&record).unwrap();
+    audit_log.append(&push).unwrap();
+
+    // Appending keeps the earlier records
+    let content = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(content.lines().count(), 2);
+    assert!(content
+        .lines()
+        .next()
+        .unwrap()
+        .contains("\"operation\":\"delete\""));
+    assert_eq!(
+        AuditLog::new(path.clone()).records().unwrap(),
+        vec![record, push]
+    );
+
+    std::fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,90 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use loki_ui::{
+    loki::error::Error,
+    ui::completion::{function_keywords, Context, Kind, LabelCache},
+};
+
+fn context(query: &str) -> Option<Context> {
+    Context::at(query, query.chars().count())
+}
+
+#[test]
+fn label_names() {
+    let context = context("{job=\"a\", inst").unwrap();
+    assert_eq!(context.kind, Kind::LabelName);
+    assert_eq!(context.prefix, "inst");
+    assert_eq!(context.start, 10);
+    assert_eq!(
+        context.matching(["instance", "job", "inst"]),
+        vec![String::from("instance")]
+    );
+}
+
+#[test]
+fn label_values() {
+    let context = context("{job=~\"ap").unwrap();
+    assert_eq!(
+        context.kind,
+        Kind::LabelValue {
+            label: String::from("job"),
+            quoted: true
+        }
+    );
+    assert_eq!(context.prefix, "ap");
+    assert_eq!(context.replacement("a\"b"), "a\\\"b");
+
+    let context = self::context("{job=").unwrap();
+    assert_eq!(context.replacement("api"), "\"api\"");
+}
+
+#[test]
+fn keywords() {
+    let context = context("{job=\"a\"} | lo").unwrap();
+    assert_eq!(context.kind, Kind::Pipeline);
+    assert_eq!(context.prefix, "lo");
+
+    let context = self::context("sum(count_ov").unwrap();
+    assert_eq!(context.kind, Kind::Function);
+    assert_eq!(context.prefix, "count_ov");
+    assert_eq!(
+        context.matching(function_keywords()),
+        vec![String::from("count_over_time")]
+    );
+    assert!(function_keywords().any(|keyword| keyword == "approx_topk"));
+
+    assert_eq!(self::context("rate({job=\"a\"}[5m"), None);
+    assert_eq!(self::context("{job=\"a\"} |= \"err"), None);
+}
+
+#[test]
+fn failed_loads_are_not_retried_on_every_lookup() {
+    let cache = Arc::new(Mutex::new(LabelCache::default()));
+    let calls = Arc::new(AtomicUsize::new(0));
+    let lookup = || {
+        let calls = calls.clone();
+        LabelCache::load(&cache, Some("job"), move || {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(Error::InvalidInput(String::from("unreachable")))
+        })
+    };
+
+    assert!(lookup().is_empty());
+    // Give the background load time to fail
+    thread::sleep(Duration::from_millis(200));
+    assert!(lookup().is_empty());
+    thread::sleep(Duration::from_millis(200));
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    cache.lock().unwrap().retry_failed();
+    lookup();
+    thread::sleep(Duration::from_millis(200));
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
@@ -0,0 +1,96 @@
+use loki_ui::{profile::Profile, EnvConfig, LokiConfig};
+
+fn vars(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+    vars.iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect()
+}
+
+#[test]
+fn env_only_applies_to_the_run() {
+    let mut stored = LokiConfig {
+        loki_url: String::from("http://loki:3100"),
+        max_lines: 100,
+        ..LokiConfig::default()
+    };
+    stored.import_profiles(vec![Profile {
+        name: String::from("dev"),
+        loki_url: String::from("http://loki:3100"),
+        ..Profile::default()
+    }]);
+
+    let env = EnvConfig::from_vars(vars(&[
+        ("LOKI_URL", "https://loki.example.com"),
+        ("LOKI_PASSWORD", "secret"),
+        ("MAX_LINES", "2000"),
+        ("HOME", "/root"),
+    ]))
+    .unwrap();
+    let mut config = stored.clone();
+    env.apply(&mut config);
+    assert_eq!(config.loki_url, "https://loki.example.com");
+    assert_eq!(config.loki_password.as_deref(), Some("secret"));
+    assert_eq!(config.max_lines, 2000);
+    assert_eq!(config.profiles, stored.profiles);
+
+    // Settings edited during the run are kept, the ones from the environment are not
+    config.max_lines = 500;
+    config.loki_tenant_id = Some(String::from("team-a"));
+    env.unapply(&mut config, &stored);
+    assert_eq!(config.loki_url, "http://loki:3100");
+    assert_eq!(config.loki_password, None);
+    assert_eq!(config.max_lines, 500);
+    assert_eq!(config.loki_tenant_id.as_deref(), Some("team-a"));
+    assert_eq!(config.profiles.len(), 1);
+
+    assert!(EnvConfig::from_vars(vars(&[("MAX_LINES", "many")])).is_err());
+}
+
+#[test]
+fn env_stays_out_of_the_active_profile() {
+    let mut stored = LokiConfig::default();
+    stored.import_profiles(vec![Profile {
+        name: String::from("prod"),
+        loki_url: String::from("http://loki:3100"),
+        loki_username: Some(String::from("admin")),
+        ..Profile::default()
+    }]);
+    stored.use_profile("prod").unwrap();
+
+    let env = EnvConfig::from_vars(vars(&[
+        ("LOKI_URL", "https://loki.example.com"),
+        ("LOKI_PASSWORD", "secret"),
+        ("LOKI_BEARER_TOKEN", "token"),
+    ]))
+    .unwrap();
+    let mut config = stored.clone();
+    env.apply(&mut config);
+
+    // Closing the settings writes them into the active profile
+    config.max_lines = 500;
+    config.update_profile();
+    env.unapply(&mut config, &stored);
+
+    let profile = config.profile().unwrap();
+    assert_eq!(profile.loki_url, "http://loki:3100");
+    assert_eq!(profile.loki_username.as_deref(), Some("admin"));
+    assert_eq!(profile.loki_password, None);
+    assert_eq!(profile.loki_bearer_token, None);
+    assert_eq!(profile.max_lines, Some(500));
+}
+
+#[test]
+fn read_only_from_env() {
+    // Only read_only_override reads LOKI_READ_ONLY, the rest of the environment still loads
+    let env = EnvConfig::from_vars(vars(&[
+        ("LOKI_URL", "https://loki.example.com"),
+        ("LOKI_READ_ONLY", "1"),
+    ]))
+    .unwrap();
+    assert_eq!(env.loki_url.as_deref(), Some("https://loki.example.com"));
+    assert!(LokiConfig::read_only_override(Vec::new(), Some("1")));
+
+    let config: LokiConfig =
+        toml::from_str("loki_url = \"http://loki:3100\"\nloki_read_only = true").unwrap();
+    assert!(!config.read_only);
+}
@@ -0,0 +1,44 @@
+use loki_ui::loki::delete::{DeleteRequest, DeleteStatus};
+use serde_json::json;
+
+#[test]
+fn list() {
+    let response = json!([
+        {
+            "request_id": "dc1e66fb",
+            "query": "{job=\"a\"}",
+            "start_time": 1700000000.5,
+            "end_time": 1700003600,
+            "created_at": 1700009000.123,
+            "status": "received"
+        },
+        {
+            "request_id": "0a1b2c3d",
+            "query": "{job=\"b\"} |= \"secret\"",
+            "start_time": 1700000000,
+            "end_time": 1700003600,
+            "created_at": 1700000000,
+            "status": "50% processed"
+        }
+    ]);
+
+    let requests = DeleteRequest::list_from_json(&response).unwrap();
+    assert_eq!(requests[0].request_id, "dc1e66fb");
+    assert_eq!(requests[0].status, DeleteStatus::Received);
+    assert_eq!(requests[0].status.to_string(), "pending");
+    assert_eq!(requests[0].start.timestamp_millis(), 1_700_000_000_500);
+    assert_eq!(requests[0].created_at.timestamp_millis(), 1_700_009_000_123);
+    assert_eq!(
+        requests[1].status,
+        DeleteStatus::Partial(String::from("50% processed"))
+    );
+}
+
+#[test]
+fn invalid_lists() {
+    assert!(DeleteRequest::list_from_json(&json!({"status": "success"})).is_err());
+    assert!(DeleteRequest::list_from_json(&json!([{"request_id": "a"}])).is_err());
+    assert!(DeleteRequest::list_from_json(&json!([]))
+        .unwrap()
+        .is_empty());
+}
@@ -0,0 +1,63 @@
+use loki_ui::{
+    loki::{error::Error, labels::Labels, result::LokiResult, Loki},
+    LokiConfig,
+};
+use serde_json::json;
+
+#[test]
+fn status_with_plain_body() {
+    let error = Error::from_status(400, String::from("parse error at line 1, col 2\n"));
+    let Error::Status { status, body } = &error else {
+        panic!("Expected a status error, got {error:?}");
+    };
+    assert_eq!(*status, 400);
+    assert_eq!(body, "parse error at line 1, col 2");
+    assert_eq!(
+        error.to_string(),
+        "Loki answered with status 400:\nparse error at line 1, col 2"
+    );
+}
+
+#[test]
+fn api_errors() {
+    let body = json!({"status": "error", "errorType": "bad_data", "error": "invalid step"});
+    let error = Error::from_status(400, body.to_string());
+    assert!(matches!(
+        error,
+        Error::Api { ref error_type, ref message } if error_type == "bad_data" && message == "invalid step"
+    ));
+
+    // Errors in an otherwise successful response are found too
+    let error = LokiResult::from_json(&body).unwrap_err();
+    assert!(matches!(error, Error::Api { .. }));
+}
+
+#[test]
+fn decode_errors() {
+    let error = LokiResult::from_json(&json!({"status": "success", "data": {}})).unwrap_err();
+    assert!(matches!(error, Error::Decode { source: None, .. }));
+    assert_eq!(error.to_string(), "Response contains no resultType");
+}
+
+#[test]
+fn read_only() {
+    // Nothing listens here, so anything but ReadOnly means a request was attempted
+    let mut loki = Loki::new(String::from("http://127.0.0.1:9"));
+    loki.read_only = true;
+
+    let error = loki.delete("{job=\"api\"}", None, None).unwrap_err();
+    assert!(matches!(error, Error::ReadOnly("deleting")));
+    assert_eq!(error.to_string(), "Read-only mode, deleting is disabled");
+    assert!(matches!(loki.cancel_delete("abc"), Err(Error::ReadOnly(_))));
+    let labels = Labels::new().with("job", "test").unwrap();
+    assert!(matches!(
+        loki.send_message(String::from("line"), &labels, None),
+        Err(Error::ReadOnly(_))
+    ));
+
+    let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+    assert!(LokiConfig::read_only_override(args(&["--read-only"]), None));
+    assert!(LokiConfig::read_only_override(args(&[]), Some("true")));
+    assert!(!LokiConfig::read_only_override(args(&[]), Some("false")));
+    assert!(!LokiConfig::read_only_override(args(&["--other"]), None));
+}
@@ -0,0 +1,39 @@
+use loki_ui::ui::highlight::{line, styles};
+use ratatui::style::{Color, Modifier};
+
+#[test]
+fn highlighting() {
+    let query = r#"rate({job="a"} |= "x" [5m])"#;
+    let styles = styles(query);
+    // The styled ranges cover the whole query
+    assert_eq!(styles.first().unwrap().0.start, 0);
+    assert_eq!(styles.last().unwrap().0.end, query.len());
+
+    let style_of = |text: &str| {
+        let start = query.find(text).unwrap();
+        styles
+            .iter()
+            .find(|(range, _)| range.start == start)
+            .unwrap()
+            .1
+    };
+    assert_eq!(style_of("rate").fg, Some(Color::Magenta));
+    assert_eq!(style_of("job").fg, Some(Color::Cyan));
+    assert_eq!(style_of("\"a\"").fg, Some(Color::Green));
+    assert_eq!(style_of("|=").fg, Some(Color::LightRed));
+    assert_eq!(style_of("5m").fg, Some(Color::LightYellow));
+}
+
+#[test]
+fn errors_and_cursor() {
+    let styles = styles(r#"{job="a} |= "x""#);
+    let (range, style) = styles.last().unwrap();
+    // The string closes at the next quote, so the last one is left open
+    assert_eq!(range.start, 14);
+    assert_eq!(style.fg, Some(Color::Red));
+
+    let line = line("{a=\"b\"}", Some(7));
+    let cursor = line.spans.last().unwrap();
+    assert_eq!(cursor.content, " ");
+    assert!(cursor.style.add_modifier.contains(Modifier::REVERSED));
+}
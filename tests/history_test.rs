@@ -0,0 +1,31 @@
+use loki_ui::ui::history::{fuzzy_match, History};
+
+#[test]
+fn history_file() {
+    let path = std::env::temp_dir().join(format!("loki_ui_history_{}.jsonl", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let mut history = History::open(path.clone());
+    assert!(history.entries().is_empty());
+    history.push("http://localhost:3100", "{job=\"a\"}");
+    history.push("http://localhost:3100", "rate({job=\"b\"}[5m])");
+    history.push("http://localhost:3100", "{job=\"a\"}");
+
+    let history = History::open(path.clone());
+    assert_eq!(history.entries().len(), 3);
+    assert_eq!(history.entries()[0].url, "http://localhost:3100");
+    assert_eq!(
+        history.queries(),
+        vec!["{job=\"a\"}", "rate({job=\"b\"}[5m])"]
+    );
+    assert_eq!(history.search("rtb"), vec!["rate({job=\"b\"}[5m])"]);
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn fuzzy() {
+    assert!(fuzzy_match("cot", "count_over_time"));
+    assert!(fuzzy_match("JOB api", "{job=\"api\"}"));
+    assert!(!fuzzy_match("tc", "count"));
+}
@@ -1,40 +1,68 @@
 use log::error;
-use loki_ui::loki::Loki;
+use loki_ui::loki::{labels::Labels, result::LokiResult, Loki, QueryOptions};
 
 #[test]
 fn main() {
     let mut loki = Loki::new(String::from("http://localhost:3100"));
-    loki.send_message(
-        String::from("Test log"),
-        String::from("{job=\"a\", instance=\"localhost\"}"),
-        None,
-    );
+    let localhost = Labels::new()
+        .with("job", "a")
+        .unwrap()
+        .with("instance", "localhost")
+        .unwrap();
+    loki.send_message(String::from("Test log"), &localhost, None)
+        .unwrap();
 
-    loki.send_message(
-        String::from("Another one"),
-        String::from("{job=\"a\", instance=\"localhost\"}"),
-        None,
-    );
+    loki.send_message(String::from("Another one"), &localhost, None)
+        .unwrap();
 
     loki.send_message(
         String::from("A different one"),
-        String::from("{job=\"a\", instance=\"someotherhost\"}"),
+        &Labels::new()
+            .with("job", "a")
+            .unwrap()
+            .with("instance", "someotherhost")
+            .unwrap(),
         None,
-    );
+    )
+    .unwrap();
 
     /*loki.send_message(String::from("Hello world test message"), String::from("{name=\"nils\", job=\"a\"}"), None);*/
 
     let results = loki
-        .query_range("count_over_time({job=\"a\"} [1h])", None, None, None)
+        .query_range(
+            "count_over_time({job=\"a\"} [1h])",
+            &QueryOptions::default(),
+            None,
+            None,
+        )
         .unwrap();
     //let results = loki.query_range("{job=\"a\"}", None, None, None).unwrap();
 
-    for (counter, result) in results.into_iter().enumerate() {
+    let LokiResult::Matrix(series) = results else {
+        panic!("Expected a matrix, got {results:?}");
+    };
+    for (counter, series) in series.into_iter().enumerate() {
         error!("Result: {counter}");
-        error!("Labels: {:?}", result.labels);
+        error!("Labels: {:?}", series.metric);
         error!("Values:");
-        for value in result.values {
+        for value in series.values {
             error!("  {value:?}");
         }
     }
+
+    let results = loki
+        .query("sum(count_over_time({job=\"a\"} [1h]))", None, None)
+        .unwrap();
+
+    let LokiResult::Vector(samples) = results else {
+        panic!("Expected a vector, got {results:?}");
+    };
+    for sample in samples {
+        error!("Instant labels: {:?}", sample.metric);
+        error!("  {:?}", sample.value);
+    }
+
+    let series = loki.series(&["{job=\"a\"}"], None, None).unwrap();
+    error!("Series: {series:?}");
+    assert!(series.iter().all(|labels| labels["job"] == "a"));
 }
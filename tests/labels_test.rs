@@ -0,0 +1,52 @@
+use std::collections::HashMap;
+
+use loki_ui::loki::{
+    error::Error,
+    labels::{escape, Labels},
+};
+use loki_ui::ui::follow::format_labels;
+
+#[test]
+fn renders_sorted_and_escaped() {
+    let labels = Labels::new()
+        .with("path", r"C:\logs")
+        .unwrap()
+        .with("app", "say \"hi\"\n")
+        .unwrap();
+    assert_eq!(
+        labels.to_string(),
+        r#"{app="say \"hi\"\n", path="C:\\logs"}"#
+    );
+    assert_eq!(Labels::new().to_string(), "{}");
+    assert_eq!(escape("tab\there"), r"tab\there");
+
+    // Labels returned by Loki render the same way, non-ASCII stays as it is
+    let stream = HashMap::from([
+        (String::from("city"), String::from("Zürich")),
+        (String::from("app"), String::from("a\"b")),
+    ]);
+    assert_eq!(format_labels(&stream), r#"{app="a\"b", city="Zürich"}"#);
+}
+
+#[test]
+fn validates_names() {
+    for name in ["job", "_private", "http_status_2xx"] {
+        assert!(Labels::new().with(name, "x").is_ok(), "{name}");
+    }
+    for name in ["", "2xx", "job-name", "job name", "jöb", "job\""] {
+        let error = Labels::new().with(name, "x").unwrap_err();
+        assert!(matches!(error, Error::InvalidInput(_)), "{name}");
+    }
+
+    let stream = HashMap::from([(String::from("bad.name"), String::from("x"))]);
+    assert!(Labels::try_from(&stream).is_err());
+}
+
+#[test]
+fn replaces_values() {
+    let mut labels = Labels::new();
+    labels.insert("job", "a").unwrap();
+    labels.insert("job", "b").unwrap();
+    assert_eq!(labels.len(), 1);
+    assert_eq!(labels.get("job"), Some("b"));
+}
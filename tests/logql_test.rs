@@ -0,0 +1,150 @@
+use std::time::Duration;
+
+use loki_ui::loki::logql::{
+    ast::{BinaryOp, CompareOp, Expr, LabelFilter, Literal, MatchOp, MetricExpr, Stage},
+    format_duration,
+    lexer::Lexer,
+    parse, parse_bytes, parse_duration,
+};
+
+#[test]
+fn log_queries() {
+    let Expr::Log(log) =
+        parse(r#"{job="api", env=~"prod|dev"} |= "error" != `debug` | json | status >= 500, duration > 1.5s | line_format "{{.msg}}""#)
+            .unwrap()
+    else {
+        panic!("expected a log query");
+    };
+    assert_eq!(log.selector.matchers.len(), 2);
+    assert_eq!(log.selector.matchers[1].op, MatchOp::Re);
+    assert_eq!(log.selector.matchers[1].value, "prod|dev");
+    assert_eq!(log.pipeline.len(), 5);
+
+    let Stage::LabelFilter {
+        filter: LabelFilter::And(_, duration),
+        ..
+    } = &log.pipeline[3]
+    else {
+        panic!("expected a label filter");
+    };
+    assert_eq!(
+        **duration,
+        LabelFilter::Compare {
+            label: String::from("duration"),
+            op: CompareOp::Gt,
+            value: Literal::Duration(Duration::from_millis(1500)),
+        }
+    );
+
+    assert!(parse(
+        r#"{job="a"} | logfmt --strict level, msg="message" | drop __error__ | decolorize"#
+    )
+    .is_ok());
+    assert!(parse(r#"{job="a"} |= "a" or "b" | addr = ip("10.0.0.0/8") | size < 10KB"#).is_ok());
+}
+
+#[test]
+fn metric_queries() {
+    let Expr::Metric(MetricExpr::Binary { op, left, .. }) = parse(
+        r#"sum by (job) (rate({job="a"} |= "error" [5m])) / on (job) group_left sum(rate({job="a"}[5m] offset 1h)) * 100"#,
+    )
+    .unwrap() else {
+        panic!("expected a binary expression");
+    };
+    // `/` and `*` share a precedence and associate to the left
+    assert_eq!(op, BinaryOp::Mul);
+    assert!(matches!(
+        *left,
+        MetricExpr::Binary {
+            op: BinaryOp::Div,
+            ..
+        }
+    ));
+
+    assert!(parse(r#"topk(5, sum(count_over_time({job="a"}[1h])) by (level))"#).is_ok());
+    assert!(parse(
+        r#"quantile_over_time(0.99, {job="a"} | json | unwrap duration(latency) [5m]) by (path)"#
+    )
+    .is_ok());
+    assert!(parse(
+        r#"label_replace(rate({job="a"}[1m]), "dst", "$1", "src", "(.*)") > bool 2 or vector(0)"#
+    )
+    .is_ok());
+}
+
+#[test]
+fn errors() {
+    let error = parse(r#"{job="a" |= "x""#).unwrap_err();
+    assert_eq!(error.span, 9..11);
+    assert_eq!(error.message, r#"expected "," or "}" but found "|=""#);
+
+    let error = parse(r#"rate({job="a"})"#).unwrap_err();
+    assert_eq!(error.span, 14..15);
+
+    let error = parse(r#"{job="a} |= "x""#).unwrap_err();
+    assert_eq!(error.message, "unterminated string");
+
+    let error = parse(r#"{job="a"} / 2"#).unwrap_err();
+    assert_eq!(error.span, 10..11);
+
+    assert_eq!(parse("unknown({a=\"b\"})").unwrap_err().span, 0..7);
+    assert!(parse("{}").is_err());
+    assert!(parse("").is_err());
+}
+
+#[test]
+fn units() {
+    assert_eq!(parse_duration("1h30m"), Some(Duration::from_secs(5400)));
+    assert_eq!(parse_duration("250ms"), Some(Duration::from_millis(250)));
+    assert_eq!(parse_duration("2d"), Some(Duration::from_secs(172800)));
+    assert_eq!(parse_duration("5"), None);
+    assert_eq!(parse_bytes("10KB"), Some(10_000));
+    assert_eq!(parse_bytes("1kib"), Some(1024));
+    assert_eq!(parse_bytes("5m"), None);
+}
+
+#[test]
+fn huge_durations() {
+    let query = r#"{a="b"}[99999999999999999999999y]"#;
+    assert_eq!(parse_duration("99999999999999999999999y"), None);
+    assert!(Lexer::new(query).any(|token| token.is_err()));
+    assert!(parse(query).is_err());
+}
+
+#[test]
+fn duration_round_trip() {
+    assert_eq!(format_duration(Duration::from_secs(5400)), "1h30m");
+    assert_eq!(format_duration(Duration::from_millis(250)), "250ms");
+    assert_eq!(format_duration(Duration::ZERO), "0s");
+    for text in ["1d2h", "90s", "15m", "1s500ms"] {
+        let duration = parse_duration(text).unwrap();
+        assert_eq!(parse_duration(&format_duration(duration)), Some(duration));
+    }
+}
+
+#[test]
+fn parenthesized_label_filter() {
+    let Expr::Log(log) =
+        parse(r#"{job="a"} | logfmt | (level="error" or level="warn") and status>=500"#).unwrap()
+    else {
+        panic!("expected a log query");
+    };
+    assert_eq!(log.pipeline.len(), 2);
+    let Stage::LabelFilter {
+        filter: LabelFilter::And(levels, _),
+        ..
+    } = &log.pipeline[1]
+    else {
+        panic!("expected a label filter");
+    };
+    assert!(matches!(**levels, LabelFilter::Or(_, _)));
+}
+
+#[test]
+fn first_selector() {
+    let query =
+        r#"sum by (job) (rate({job="a"} |= "error" [5m])) / count_over_time({job="b"}[5m])"#;
+    let selector = parse(query).unwrap().selector().unwrap().span.clone();
+    assert_eq!(&query[selector], r#"{job="a"}"#);
+    assert!(parse("vector(1)").unwrap().selector().is_none());
+}
@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+
+use loki_ui::loki::{
+    paging::{Next, Pages},
+    result::{LokiResult, LokiStream, LokiValue},
+    Direction,
+};
+
+fn stream(job: &str, values: &[(i64, &str)]) -> LokiStream {
+    LokiStream {
+        labels: HashMap::from([(String::from("job"), job.to_string())]),
+        values: values
+            .iter()
+            .map(|(ns, line)| LokiValue::from_nano(ns.to_string(), line.to_string()).unwrap())
+            .collect(),
+    }
+}
+
+fn lines(result: &LokiResult) -> Vec<String> {
+    let LokiResult::Streams(streams) = result else {
+        panic!("Expected streams");
+    };
+    streams
+        .iter()
+        .flat_map(|stream| stream.values.iter().map(|value| value.log_line.clone()))
+        .collect()
+}
+
+fn nanos(next: Next) -> i64 {
+    match next {
+        Next::Page(time) => time.timestamp_nanos_opt().unwrap(),
+        Next::Done => panic!("Expected another page"),
+    }
+}
+
+#[test]
+fn backward_dedupes_the_boundary() {
+    let mut pages = Pages::new(Direction::Backward, 100);
+
+    let next = pages.add(vec![stream("a", &[(30, "c"), (20, "b")])], 2);
+    // The end is exclusive, so the next page ends right after the oldest line
+    assert_eq!(nanos(next), 21);
+
+    let next = pages.add(vec![stream("a", &[(20, "b"), (10, "a")])], 2);
+    assert_eq!(nanos(next), 11);
+    assert_eq!(pages.add(vec![stream("a", &[(10, "a")])], 2), Next::Done);
+
+    let result = pages.finish();
+    assert_eq!(result.lines, 3);
+    assert!(!result.truncated);
+    assert_eq!(lines(&result.result), vec!["c", "b", "a"]);
+}
+
+#[test]
+fn forward_keeps_lines_sharing_a_timestamp() {
+    let mut pages = Pages::new(Direction::Forward, 100);
+
+    let next = pages.add(vec![stream("a", &[(10, "a"), (20, "b")])], 2);
+    assert_eq!(nanos(next), 20);
+
+    // Another line at the boundary timestamp is kept, the repeated one is dropped
+    let next = pages.add(vec![stream("a", &[(20, "b"), (20, "b2")])], 2);
+    assert_eq!(nanos(next), 20);
+
+    // Nothing new at the boundary, so step past it
+    let next = pages.add(vec![stream("a", &[(20, "b"), (20, "b2")])], 2);
+    assert_eq!(nanos(next), 21);
+
+    assert_eq!(pages.add(vec![stream("a", &[(30, "c")])], 2), Next::Done);
+    let result = pages.finish();
+    assert_eq!(result.lines, 4);
+    let mut found = lines(&result.result);
+    found[1..3].sort();
+    assert_eq!(found, vec!["a", "b", "b2", "c"]);
+}
+
+#[test]
+fn stops_at_max_lines() {
+    let mut pages = Pages::new(Direction::Backward, 3);
+
+    let next = pages.add(
+        vec![stream("a", &[(40, "d")]), stream("b", &[(30, "c")])],
+        2,
+    );
+    assert_eq!(nanos(next), 31);
+    let next = pages.add(
+        vec![stream("a", &[(20, "b")]), stream("b", &[(10, "a")])],
+        2,
+    );
+    assert_eq!(next, Next::Done);
+
+    let result = pages.finish();
+    assert_eq!(result.lines, 3);
+    assert!(result.truncated);
+    // The newest lines are kept when paging backward
+    let mut found = lines(&result.result);
+    found.sort();
+    assert_eq!(found, vec!["b", "c", "d"]);
+}
@@ -0,0 +1,113 @@
+use loki_ui::{
+    loki::Credentials,
+    profile::{from_grafana_datasources, Profile},
+    LokiConfig,
+};
+
+#[test]
+fn grafana_datasources() {
+    let content = r#"
+apiVersion: 1
+datasources:
+  - name: Prometheus
+    type: prometheus
+    url: http://prometheus:9090
+  - name: Loki
+    url: http://loki:3100
+    type: loki
+  - name: prod
+    type: loki
+    url: https://loki.example.com/
+    basicAuth: true
+    basicAuthUser: support
+    jsonData:
+      maxLines: 1000
+      httpHeaderName1: X-Scope-OrgID
+    secureJsonData:
+      basicAuthPassword: secret
+      httpHeaderValue1: team-a
+"#;
+    let profiles = from_grafana_datasources(content).unwrap();
+    assert_eq!(profiles.len(), 2);
+    assert_eq!(
+        profiles[0],
+        Profile {
+            name: String::from("Loki"),
+            loki_url: String::from("http://loki:3100"),
+            ..Profile::default()
+        }
+    );
+    assert_eq!(profiles[1].loki_url, "https://loki.example.com");
+    assert_eq!(profiles[1].loki_username.as_deref(), Some("support"));
+    assert_eq!(profiles[1].loki_password.as_deref(), Some("secret"));
+    assert_eq!(profiles[1].loki_tenant_id.as_deref(), Some("team-a"));
+
+    // The provisioning file of this repository
+    let repository = include_str!("../loki.yaml");
+    assert_eq!(from_grafana_datasources(repository).unwrap().len(), 1);
+
+    assert!(from_grafana_datasources("datasources: []").is_err());
+    assert!(from_grafana_datasources("datasources:\n  - name: x\n    type: loki").is_err());
+}
+
+#[test]
+fn switching_profiles() {
+    let mut config = LokiConfig {
+        max_lines: 100,
+        ..LokiConfig::default()
+    };
+    config.import_profiles(vec![
+        Profile {
+            name: String::from("dev"),
+            loki_url: String::from("http://localhost:3100"),
+            ..Profile::default()
+        },
+        Profile {
+            name: String::from("prod"),
+            loki_url: String::from("https://loki.example.com"),
+            loki_bearer_token: Some(String::from("token")),
+            loki_tenant_id: Some(String::from("team-a")),
+            read_only: true,
+            max_lines: Some(2000),
+            ..Profile::default()
+        },
+    ]);
+    assert!(config.use_profile("staging").is_err());
+
+    config.use_profile("prod").unwrap();
+    assert_eq!(config.active_profile.as_deref(), Some("prod"));
+    assert_eq!(config.max_lines, 2000);
+    let loki = config.loki();
+    assert_eq!(loki.address, "https://loki.example.com");
+    assert_eq!(loki.tenant_id.as_deref(), Some("team-a"));
+    assert!(matches!(loki.credentials, Credentials::Bearer(_)));
+    assert!(loki.read_only);
+
+    // Defaults the profile doesn't set are kept
+    config.use_profile("dev").unwrap();
+    assert_eq!(config.max_lines, 2000);
+    assert_eq!(config.loki_tenant_id, None);
+    assert!(!config.loki().read_only);
+
+    // Edited settings are written back into the active profile
+    config.loki_tenant_id = Some(String::from("team-b"));
+    config.update_profile();
+    assert_eq!(
+        config.profile().unwrap().loki_tenant_id.as_deref(),
+        Some("team-b")
+    );
+
+    // Importing replaces profiles with the same name
+    config.import_profiles(vec![Profile {
+        name: String::from("dev"),
+        loki_url: String::from("http://loki:3100"),
+        ..Profile::default()
+    }]);
+    assert_eq!(config.profiles.len(), 2);
+    assert_eq!(config.profile().unwrap().loki_url, "http://loki:3100");
+
+    // The config file keeps the profiles
+    let stored: LokiConfig = toml::from_str(&toml::to_string(&config).unwrap()).unwrap();
+    assert_eq!(stored.profiles, config.profiles);
+    assert_eq!(stored.active_profile.as_deref(), Some("dev"));
+}
@@ -0,0 +1,98 @@
+use std::time::Duration;
+
+use chrono::{Local, TimeZone};
+use loki_ui::loki::{
+    error::Error,
+    labels::Labels,
+    proto::{LabelPairAdapter, StreamAdapter},
+    push::{Batch, PushEntry, PushStream},
+    Loki,
+};
+
+fn job(job: &str) -> Labels {
+    Labels::new().with("job", job).unwrap()
+}
+
+/// Nothing listens on the discard port, so every push fails right away
+fn unreachable() -> Loki {
+    Loki::new(String::from("http://127.0.0.1:9"))
+}
+
+#[test]
+fn streams_keep_nanoseconds() {
+    let stream = PushStream {
+        labels: Labels::new().with("job", "a").unwrap(),
+        entries: vec![PushEntry::new(
+            Local.timestamp_nanos(1_700_000_000_123_456_789),
+            String::from("line"),
+        )],
+    };
+    let adapter = StreamAdapter::from(&stream);
+    let timestamp = adapter.entries[0].timestamp.as_ref().unwrap();
+    assert_eq!(timestamp.seconds, 1_700_000_000);
+    assert_eq!(timestamp.nanos, 123_456_789);
+}
+
+#[test]
+fn streams_carry_structured_metadata() {
+    let stream = PushStream {
+        labels: Labels::new().with("job", "a").unwrap(),
+        entries: vec![PushEntry::now(String::from("line"))
+            .with_metadata("trace_id", "abc")
+            .with_metadata("span_id", "def")],
+    };
+    let adapter = StreamAdapter::from(&stream);
+    assert_eq!(
+        adapter.entries[0].structured_metadata,
+        vec![
+            LabelPairAdapter {
+                name: String::from("span_id"),
+                value: String::from("def"),
+            },
+            LabelPairAdapter {
+                name: String::from("trace_id"),
+                value: String::from("abc"),
+            },
+        ]
+    );
+}
+
+#[test]
+fn batch_flushes_when_full() {
+    let mut batch = Batch::new(unreachable());
+    batch.max_bytes = 20;
+    batch.max_wait = Duration::from_secs(3600);
+
+    batch
+        .add(&job("a"), PushEntry::now(String::from("one")))
+        .unwrap();
+    assert_eq!(batch.len(), 1);
+
+    // The labels and both lines are over 20 bytes, so the batch is sent and fails
+    let error = batch
+        .add(&job("a"), PushEntry::now(String::from("two two two")))
+        .unwrap_err();
+    assert!(matches!(error, Error::Transport(_)));
+    // Failed lines are kept for another try
+    assert_eq!(batch.len(), 2);
+    assert!(batch.flush().is_err());
+}
+
+#[test]
+fn batch_flushes_after_waiting() {
+    let mut batch = Batch::new(unreachable());
+    batch.max_wait = Duration::ZERO;
+    assert!(batch
+        .add(&job("a"), PushEntry::now(String::from("one")))
+        .is_err());
+
+    let mut batch = Batch::new(unreachable());
+    batch
+        .add(&job("a"), PushEntry::now(String::from("one")))
+        .unwrap();
+    batch
+        .add(&job("b"), PushEntry::now(String::from("two")))
+        .unwrap();
+    assert_eq!(batch.len(), 2);
+    assert!(Batch::new(unreachable()).flush().is_ok());
+}
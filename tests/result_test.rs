@@ -0,0 +1,164 @@
+use loki_ui::loki::{
+    result::{LokiResult, LokiValue},
+    time::to_nanos,
+};
+use serde_json::json;
+
+#[test]
+fn streams() {
+    let response = json!({
+        "status": "success",
+        "data": {
+            "resultType": "streams",
+            "result": [{
+                "stream": {"job": "a"},
+                "values": [["1700000000123456789", "Test log"]]
+            }]
+        }
+    });
+
+    let LokiResult::Streams(streams) = LokiResult::from_json(&response).unwrap() else {
+        panic!("Expected streams");
+    };
+    assert_eq!(streams[0].labels["job"], "a");
+    assert_eq!(streams[0].values[0].log_line, "Test log");
+    assert_eq!(
+        streams[0].values[0].timestamp.timestamp_nanos_opt(),
+        Some(1_700_000_000_123_456_789)
+    );
+}
+
+#[test]
+fn streams_with_structured_metadata() {
+    let response = json!({
+        "status": "success",
+        "data": {
+            "resultType": "streams",
+            "encodingFlags": ["categorize-labels"],
+            "result": [{
+                "stream": {"job": "a"},
+                "values": [
+                    ["1700000000000000000", "first", {
+                        "structuredMetadata": {"trace_id": "abc"},
+                        "parsed": {"level": "info"}
+                    }],
+                    ["1700000000000000001", "second", {"trace_id": "def"}],
+                    ["1700000000000000002", "third"]
+                ]
+            }]
+        }
+    });
+
+    let LokiResult::Streams(streams) = LokiResult::from_json(&response).unwrap() else {
+        panic!("Expected streams");
+    };
+    let values = &streams[0].values;
+    assert_eq!(values[0].structured_metadata["trace_id"], "abc");
+    assert_eq!(values[0].parsed["level"], "info");
+    // Older Loki versions send the metadata without categories
+    assert_eq!(values[1].structured_metadata["trace_id"], "def");
+    assert!(values[1].parsed.is_empty());
+    assert!(values[2].structured_metadata.is_empty());
+    assert!(!streams[0].labels.contains_key("trace_id"));
+}
+
+#[test]
+fn matrix_with_float_timestamps() {
+    let response = json!({
+        "status": "success",
+        "data": {
+            "resultType": "matrix",
+            "result": [{
+                "metric": {"job": "a"},
+                "values": [[1700000000.5, "2"], [1700000060, "3.25"]]
+            }]
+        }
+    });
+
+    let LokiResult::Matrix(series) = LokiResult::from_json(&response).unwrap() else {
+        panic!("Expected a matrix");
+    };
+    assert_eq!(series[0].metric["job"], "a");
+    assert_eq!(series[0].values[0].value, 2.0);
+    assert_eq!(
+        series[0].values[0].timestamp.timestamp_millis(),
+        1_700_000_000_500
+    );
+    assert_eq!(series[0].values[1].value, 3.25);
+}
+
+#[test]
+fn vector_and_scalar() {
+    let response = json!({
+        "status": "success",
+        "data": {
+            "resultType": "vector",
+            "result": [{"metric": {}, "value": [1700000000, "NaN"]}]
+        }
+    });
+    let LokiResult::Vector(samples) = LokiResult::from_json(&response).unwrap() else {
+        panic!("Expected a vector");
+    };
+    assert!(samples[0].value.value.is_nan());
+
+    let response = json!({
+        "status": "success",
+        "data": {"resultType": "scalar", "result": [1700000000, "42"]}
+    });
+    let LokiResult::Scalar(sample) = LokiResult::from_json(&response).unwrap() else {
+        panic!("Expected a scalar");
+    };
+    assert_eq!(sample.value, 42.0);
+}
+
+#[test]
+fn unknown_shapes_are_errors() {
+    let response = json!({"data": {"resultType": "string", "result": []}});
+    let error = LokiResult::from_json(&response).unwrap_err();
+    assert!(error.to_string().contains("Unknown result type: string"));
+
+    let response = json!({"data": {"resultType": "streams", "result": [{"values": []}]}});
+    assert!(LokiResult::from_json(&response).is_err());
+
+    let response = json!({"status": "error"});
+    assert!(LokiResult::from_json(&response).is_err());
+}
+
+#[test]
+fn nanosecond_round_trip() {
+    for timestamp in ["1706039902123456789", "1706039902000000001", "0"] {
+        let value = LokiValue::from_nano(timestamp.to_string(), String::from("line")).unwrap();
+        assert_eq!(to_nanos(&value.timestamp), timestamp);
+    }
+
+    // Lines within the same second keep their order
+    let first = LokiValue::from_nano(String::from("1706039902000000001"), String::new()).unwrap();
+    let second = LokiValue::from_nano(String::from("1706039902000000002"), String::new()).unwrap();
+    assert!(first.timestamp < second.timestamp);
+
+    assert!(LokiValue::from_nano(String::from("yesterday"), String::new()).is_err());
+}
+
+#[test]
+fn sample_timestamps_round_into_the_next_second() {
+    let response = json!({
+        "status": "success",
+        "data": {
+            "resultType": "vector",
+            "result": [{"metric": {}, "value": [1.9999999996, "1"]}]
+        }
+    });
+    let LokiResult::Vector(samples) = LokiResult::from_json(&response).unwrap() else {
+        panic!("Expected a vector");
+    };
+    assert_eq!(
+        samples[0].value.timestamp.timestamp_nanos_opt(),
+        Some(2_000_000_000)
+    );
+
+    let response = json!({
+        "status": "success",
+        "data": {"resultType": "scalar", "result": [1e300, "1"]}
+    });
+    assert!(LokiResult::from_json(&response).is_err());
+}
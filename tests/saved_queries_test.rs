@@ -0,0 +1,58 @@
+use loki_ui::{loki::time::TimeRange, ui::saved_queries::SavedQueries};
+
+#[test]
+fn toml_and_yaml() {
+    let toml = SavedQueries::from_toml(
+        r#"
+[[queries]]
+name = "Errors"
+description = "All errors of the api"
+query = '{job="api"} |= "error"'
+range = "1h"
+limit = 500
+
+[[queries]]
+name = "Rate"
+query = 'rate({job="api"}[5m])'
+"#,
+    )
+    .unwrap();
+    let yaml = SavedQueries::from_yaml(
+        r#"
+queries:
+  - name: Errors
+    description: All errors of the api
+    query: '{job="api"} |= "error"'
+    range: 1h
+    limit: 500
+  - name: Rate
+    query: 'rate({job="api"}[5m])'
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(toml.queries, yaml.queries);
+    assert_eq!(
+        toml.queries[0].time_range(),
+        Some(TimeRange::new("now-1h", "now"))
+    );
+    assert_eq!(toml.queries[0].limit, Some(500));
+    assert_eq!(toml.queries[1].time_range(), None);
+    assert_eq!(toml.queries[1].description, "");
+}
+
+#[test]
+fn invalid_range() {
+    let path = std::env::temp_dir().join(format!("loki_ui_saved_{}.yaml", std::process::id()));
+    // Ranges too large for a duration or a time are errors, not panics
+    for range in ["soon", "99999999999999999999999y", "300000y"] {
+        std::fs::write(
+            &path,
+            format!("queries:\n  - name: Broken\n    query: '{{a=\"b\"}}'\n    range: {range}\n"),
+        )
+        .unwrap();
+        let error = SavedQueries::load(&path).unwrap_err();
+        assert!(error.contains(range));
+    }
+    std::fs::remove_file(path).unwrap();
+}
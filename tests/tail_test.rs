@@ -0,0 +1,69 @@
+use std::{net::TcpListener, thread};
+
+use loki_ui::loki::Loki;
+use tungstenite::{
+    handshake::server::{Request, Response},
+    Message,
+};
+
+/// Stands in for Loki's tail endpoint, sending a single batch of lines
+// The handshake callback has to return tungstenite's error response
+#[allow(clippy::result_large_err)]
+fn serve_once(listener: TcpListener) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        let mut uri = String::new();
+        let mut socket =
+            tungstenite::accept_hdr(stream, |request: &Request, response: Response| {
+                assert_eq!(request.headers()["X-Scope-OrgID"], "tenant-a");
+                uri = request.uri().to_string();
+                Ok(response)
+            })
+            .unwrap();
+
+        socket
+            .send(Message::text(
+                r#"{
+                    "streams": [{
+                        "stream": {"job": "a"},
+                        "values": [["1700000000000000001", "first"], ["1700000000000000002", "second"]]
+                    }],
+                    "dropped_entries": [{"labels": {"job": "a"}, "timestamp": "1699999999000000000"}]
+                }"#,
+            ))
+            .unwrap();
+        socket.close(None).unwrap();
+        // Drive the close handshake until the client answers
+        while socket.read().is_ok() {}
+        uri
+    })
+}
+
+#[test]
+fn tail() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let server = serve_once(listener);
+
+    let mut loki = Loki::new(format!("http://{address}"));
+    loki.tenant_id = Some(String::from("tenant-a"));
+    let mut tail = loki.tail("{job=\"a\"}", Some(2), None, None).unwrap();
+
+    let response = tail.read().unwrap().unwrap();
+    assert_eq!(response.streams.len(), 1);
+    let lines: Vec<_> = response.streams[0]
+        .values
+        .iter()
+        .map(|value| value.log_line.as_str())
+        .collect();
+    assert_eq!(lines, ["first", "second"]);
+    assert_eq!(response.dropped_entries.len(), 1);
+    assert_eq!(response.dropped_entries[0].labels["job"], "a");
+
+    assert!(tail.read().is_err());
+    tail.close();
+
+    let uri = server.join().unwrap();
+    assert!(uri.starts_with("/loki/api/v1/tail?query=%7Bjob%3D%22a%22%7D"));
+    assert!(uri.contains("delay_for=2"));
+}
@@ -0,0 +1,78 @@
+use chrono::{Duration, Local, TimeZone, Timelike};
+use loki_ui::loki::{
+    error::Error,
+    time::{parse_time, TimeRange},
+};
+
+#[test]
+fn relative_times() {
+    let now = Local.with_ymd_and_hms(2024, 1, 23, 20, 58, 22).unwrap();
+    assert_eq!(parse_time("now", now, false).unwrap(), now);
+    assert_eq!(
+        parse_time("now-15m", now, false).unwrap(),
+        now - Duration::minutes(15)
+    );
+    assert_eq!(
+        parse_time("now+1h30m", now, false).unwrap(),
+        now + Duration::minutes(90)
+    );
+    assert_eq!(
+        parse_time("now-2d/d", now, false).unwrap(),
+        Local.with_ymd_and_hms(2024, 1, 21, 0, 0, 0).unwrap()
+    );
+
+    // Rounding up ends at the last nanosecond of the unit
+    let end_of_day = parse_time("now/d", now, true).unwrap();
+    assert_eq!((end_of_day.hour(), end_of_day.minute()), (23, 59));
+    assert_eq!(end_of_day.nanosecond(), 999_999_999);
+
+    // 2024-01-23 is a tuesday
+    assert_eq!(
+        parse_time("now/w", now, false).unwrap(),
+        Local.with_ymd_and_hms(2024, 1, 22, 0, 0, 0).unwrap()
+    );
+    assert!(parse_time("now-5x", now, false).is_err());
+    assert!(parse_time("now*5m", now, false).is_err());
+}
+
+#[test]
+fn out_of_range_times() {
+    let now = Local.with_ymd_and_hms(2024, 1, 23, 20, 58, 22).unwrap();
+    for expr in ["now-300000y", "now+99999999y"] {
+        assert!(matches!(
+            parse_time(expr, now, true),
+            Err(Error::InvalidInput(_))
+        ));
+    }
+    assert!(TimeRange::new("now-300000y", "now").resolve().is_err());
+}
+
+#[test]
+fn absolute_times() {
+    let now = Local::now();
+    let expected = Local.timestamp_opt(1706039902, 0).unwrap();
+    assert_eq!(parse_time("1706039902", now, false).unwrap(), expected);
+    assert_eq!(parse_time("1706039902000", now, false).unwrap(), expected);
+    assert_eq!(
+        parse_time("1706039902000000000", now, false).unwrap(),
+        expected
+    );
+    assert_eq!(
+        parse_time("2024-01-23T19:58:22Z", now, false).unwrap(),
+        expected
+    );
+    assert_eq!(
+        parse_time("2024-01-23 20:58", now, false).unwrap(),
+        Local.with_ymd_and_hms(2024, 1, 23, 20, 58, 0).unwrap()
+    );
+    assert!(parse_time("yesterday", now, false).is_err());
+}
+
+#[test]
+fn ranges() {
+    let (from, to) = TimeRange::default().resolve().unwrap();
+    assert_eq!(to - from, Duration::hours(6));
+    assert_eq!(TimeRange::last("15m").to_string(), "now-15m to now");
+    assert!(TimeRange::new("now", "now-1h").resolve().is_err());
+    assert!(TimeRange::new("now-1d/d", "now-1d/d").resolve().is_ok());
+}